@@ -0,0 +1,129 @@
+use crate::FullAdder;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::XorGate;
+use std::cell::RefCell;
+use std::iter::zip;
+use std::rc::Rc;
+
+/// A device that can add or subtract two n-bit integers, which may be unsigned or two's complement.
+///
+/// While `subtract` is high, every bit of `input_b` is inverted by an XOR gate and `subtract` is
+/// also the carry into the chain of full adders, so `sum` is `input_a + !input_b + 1`, which is
+/// `input_a - input_b`. `carry_out` is the carry out of the last adder. For unsigned addition it's
+/// high on overflow, and for unsigned subtraction it's high when there's no borrow, i.e. when
+/// `input_a >= input_b`. `signed_overflow` is high when the result doesn't fit in n bits when the
+/// operands are treated as two's complement, which is when the carries into and out of the last
+/// adder differ.
+#[derive(Device)]
+pub struct AdderSubtractor {
+    #[children]
+    invert_gates: Vec<XorGate>,
+    #[children]
+    adders: Vec<FullAdder>,
+    #[child]
+    overflow_gate: XorGate,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    subtract: Rc<RefCell<Pin>>,
+    #[pins]
+    sum: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    carry_out: Rc<RefCell<Pin>>,
+    #[pin]
+    signed_overflow: Rc<RefCell<Pin>>,
+}
+
+impl AdderSubtractor {
+    /// Creates a new `AdderSubtractor` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("AdderSubtractor width must be non-zero.")
+        }
+
+        let invert_gates: Vec<XorGate> = (0..width).map(|_| XorGate::new()).collect();
+        let adders: Vec<FullAdder> = (0..width).map(|_| FullAdder::new()).collect();
+        let overflow_gate = XorGate::new();
+        let input_a: Vec<Rc<RefCell<Pin>>> = adders.iter().map(|a| a.get_a().clone()).collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> = invert_gates
+            .iter()
+            .map(|g| g.get_b_input().clone())
+            .collect();
+        let subtract = adders[0].get_carry_in().clone();
+        let sum: Vec<Rc<RefCell<Pin>>> = adders.iter().map(|a| a.get_sum().clone()).collect();
+        let carry_out = adders.last().unwrap().get_carry().clone();
+        let signed_overflow = overflow_gate.get_output().clone();
+
+        for (invert_gate, adder) in zip(invert_gates.iter(), adders.iter()) {
+            Pin::connect(&subtract, invert_gate.get_a_input());
+            Pin::connect(invert_gate.get_output(), adder.get_b());
+        }
+        zip(adders.iter(), adders[1..].iter()).for_each(|(a0, a1)| {
+            Pin::connect(a0.get_carry(), a1.get_carry_in());
+        });
+        Pin::connect(
+            adders.last().unwrap().get_carry_in(),
+            overflow_gate.get_a_input(),
+        );
+        Pin::connect(&carry_out, overflow_gate.get_b_input());
+
+        Self {
+            invert_gates,
+            adders,
+            overflow_gate,
+            input_a,
+            input_b,
+            subtract,
+            sum,
+            carry_out,
+            signed_overflow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::check_integer_truth_table;
+
+    #[test]
+    fn test_adder_subtractor_logic() {
+        for width in 1..=5 {
+            test_adder_subtractor_n_bit(width);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_adder_subtractor() {
+        AdderSubtractor::new(0);
+    }
+
+    // Checks every row of the truth table of an n-bit adder/subtractor, treating the inputs as
+    // unsigned for the carry out and as two's complement for the signed overflow.
+    fn test_adder_subtractor_n_bit(width: usize) {
+        let modulus = 1 << width;
+        let signed = |value: usize| match value >= modulus / 2 {
+            true => value as i64 - modulus as i64,
+            false => value as i64,
+        };
+        let mismatches = check_integer_truth_table(
+            AdderSubtractor::new(width),
+            &["input_a", "input_b", "subtract"],
+            &["sum", "carry_out", "signed_overflow"],
+            |values| {
+                let (a, b, subtract) = (values[0], values[1], values[2] == 1);
+                let (sum, carry_out, signed_result) = match subtract {
+                    true => ((a + modulus - b) % modulus, a >= b, signed(a) - signed(b)),
+                    false => ((a + b) % modulus, a + b >= modulus, signed(a) + signed(b)),
+                };
+                let signed_overflow = signed_result != signed(sum);
+                vec![sum, carry_out as usize, signed_overflow as usize]
+            },
+        );
+        assert_eq!(mismatches, []);
+    }
+}
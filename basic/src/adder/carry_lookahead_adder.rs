@@ -0,0 +1,174 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, OrGate, XorGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The number of bits in each lookahead block.
+const BLOCK_WIDTH: usize = 4;
+
+/// A device that can add two n-bit unsigned integers and a carry bit, faster than a
+/// `RippleCarryAdder`.
+///
+/// Each bit generates a carry if both inputs are high, and propagates the carry into it if exactly
+/// one is. The bits are grouped into blocks of four, and within a block every carry is computed
+/// directly from the generate and propagate signals and the carry into the block, so the carry
+/// only ripples from block to block rather than from bit to bit.
+#[derive(Device)]
+pub struct CarryLookaheadAdder {
+    #[children]
+    propagate_gates: Vec<XorGate>,
+    #[children]
+    generate_gates: Vec<AndGate>,
+    #[children]
+    carry_and_gates: Vec<AndGate>,
+    #[children]
+    carry_or_gates: Vec<OrGate>,
+    #[children]
+    sum_gates: Vec<XorGate>,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    carry_in: Rc<RefCell<Pin>>,
+    #[pins]
+    sum: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    overflow: Rc<RefCell<Pin>>,
+}
+
+impl CarryLookaheadAdder {
+    /// Creates a new `CarryLookaheadAdder` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("CarryLookaheadAdder width must be non-zero.")
+        }
+
+        let propagate_gates: Vec<XorGate> = (0..width).map(|_| XorGate::new()).collect();
+        let generate_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let sum_gates: Vec<XorGate> = (0..width).map(|_| XorGate::new()).collect();
+        let input_a: Vec<Rc<RefCell<Pin>>> = propagate_gates
+            .iter()
+            .map(|g| g.get_a_input().clone())
+            .collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> = propagate_gates
+            .iter()
+            .map(|g| g.get_b_input().clone())
+            .collect();
+        let carry_in = sum_gates[0].get_b_input().clone();
+        let sum: Vec<Rc<RefCell<Pin>>> = sum_gates.iter().map(|g| g.get_output().clone()).collect();
+
+        for (index, generate_gate) in generate_gates.iter().enumerate() {
+            Pin::connect(&input_a[index], &generate_gate.get_input()[0]);
+            Pin::connect(&input_b[index], &generate_gate.get_input()[1]);
+            Pin::connect(
+                propagate_gates[index].get_output(),
+                sum_gates[index].get_a_input(),
+            );
+        }
+
+        // Within a block, the carry out of bit j is high if bit j generates a carry, or if some
+        // earlier bit m (or the carry into the block) does and every bit from m + 1 to j propagates
+        // it. Each of those conditions is an AND gate, and they're combined with an OR gate.
+        let mut carry_and_gates: Vec<AndGate> = Vec::new();
+        let mut carry_or_gates: Vec<OrGate> = Vec::new();
+        for block_start in (0..width).step_by(BLOCK_WIDTH) {
+            let block_carry_in = match block_start {
+                0 => carry_in.clone(),
+                _ => carry_or_gates[block_start - 1].get_output().clone(),
+            };
+            for bit in block_start..(block_start + BLOCK_WIDTH).min(width) {
+                let or_gate = OrGate::new(bit - block_start + 2);
+                Pin::connect(generate_gates[bit].get_output(), &or_gate.get_input()[0]);
+                for (term, first) in (block_start..=bit).rev().enumerate() {
+                    // Each term ANDs the propagate signals from `first` to `bit` with the generate
+                    // signal of the bit before `first`, or with the carry into the block.
+                    let and_gate = AndGate::new(bit - first + 2);
+                    let source = match first.checked_sub(1) {
+                        Some(previous) if previous >= block_start => {
+                            generate_gates[previous].get_output().clone()
+                        }
+                        _ => block_carry_in.clone(),
+                    };
+                    Pin::connect(&source, &and_gate.get_input()[0]);
+                    for (input, propagate) in (first..=bit).enumerate() {
+                        Pin::connect(
+                            propagate_gates[propagate].get_output(),
+                            &and_gate.get_input()[input + 1],
+                        );
+                    }
+                    Pin::connect(and_gate.get_output(), &or_gate.get_input()[term + 1]);
+                    carry_and_gates.push(and_gate);
+                }
+                if bit + 1 < width {
+                    Pin::connect(or_gate.get_output(), sum_gates[bit + 1].get_b_input());
+                }
+                carry_or_gates.push(or_gate);
+            }
+        }
+        let overflow = carry_or_gates.last().unwrap().get_output().clone();
+
+        Self {
+            propagate_gates,
+            generate_gates,
+            carry_and_gates,
+            carry_or_gates,
+            sum_gates,
+            input_a,
+            input_b,
+            carry_in,
+            sum,
+            overflow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RippleCarryAdder;
+    use foundation::{check_integer_truth_table, TimingAnalysis};
+
+    #[test]
+    fn test_carry_lookahead_adder_logic() {
+        for width in 1..=5 {
+            test_carry_lookahead_adder_n_bit(width);
+        }
+    }
+
+    // Only the carry between blocks ripples, so the critical path grows much more slowly with the
+    // width than that of a ripple-carry adder.
+    #[test]
+    fn test_carry_lookahead_adder_critical_path() {
+        for width in [8, 16] {
+            let lookahead = TimingAnalysis::new(&CarryLookaheadAdder::new(width));
+            let ripple = TimingAnalysis::new(&RippleCarryAdder::new(width));
+            let lookahead_stages = lookahead.get_critical_path().unwrap().get_stages();
+            let ripple_stages = ripple.get_critical_path().unwrap().get_stages();
+            assert!(lookahead_stages * 2 < ripple_stages);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_carry_lookahead_adder() {
+        CarryLookaheadAdder::new(0);
+    }
+
+    // Checks every combination of inputs and carry in of an n-bit carry-lookahead adder against
+    // integer addition.
+    fn test_carry_lookahead_adder_n_bit(width: usize) {
+        let max_value = 1 << width;
+        let mismatches = check_integer_truth_table(
+            CarryLookaheadAdder::new(width),
+            &["input_a", "input_b", "carry_in"],
+            &["sum", "overflow"],
+            |values| {
+                let total = values[0] + values[1] + values[2];
+                vec![total % max_value, (total >= max_value) as usize]
+            },
+        );
+        assert_eq!(mismatches, []);
+    }
+}
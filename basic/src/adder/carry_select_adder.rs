@@ -0,0 +1,284 @@
+use crate::FullAdder;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::iter::zip;
+use std::rc::Rc;
+
+/// A device that can add two n-bit unsigned integers and a carry bit, faster than a
+/// `RippleCarryAdder`.
+///
+/// The bits are split into blocks. The first block is a chain of full adders, like a
+/// `RippleCarryAdder`. Every other block has two chains, one which assumes the carry into the
+/// block is low and one which assumes it's high, and both start adding straight away. When the
+/// real carry arrives from the previous block it only has to select which result to use, so the
+/// carry passes through each block in a few stages rather than two per bit, at the cost of roughly
+/// doubling the size of the adder.
+#[derive(Device)]
+pub struct CarrySelectAdder {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    strong_true: Constant,
+    #[children]
+    adders: Vec<FullAdder>,
+    #[children]
+    blocks: Vec<SelectBlock>,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    carry_in: Rc<RefCell<Pin>>,
+    #[pins]
+    sum: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    overflow: Rc<RefCell<Pin>>,
+}
+
+impl CarrySelectAdder {
+    /// Creates a new `CarrySelectAdder` of the desired width, split into blocks of the desired
+    /// width. The last block is narrower if `width` isn't a multiple of `block`.
+    pub fn new(width: usize, block: usize) -> Self {
+        if width == 0 {
+            panic!("CarrySelectAdder width must be non-zero.")
+        }
+        if block == 0 {
+            panic!("CarrySelectAdder block must be non-zero.")
+        }
+
+        let strong_false = Constant::new_strong(false);
+        let strong_true = Constant::new_strong(true);
+        let adders: Vec<FullAdder> = (0..block.min(width)).map(|_| FullAdder::new()).collect();
+        let blocks: Vec<SelectBlock> = (block..width)
+            .step_by(block)
+            .map(|start| SelectBlock::new(block.min(width - start), &strong_false, &strong_true))
+            .collect();
+        let input_a: Vec<Rc<RefCell<Pin>>> = adders
+            .iter()
+            .map(|a| a.get_a().clone())
+            .chain(blocks.iter().flat_map(|b| b.get_input_a().to_vec()))
+            .collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> = adders
+            .iter()
+            .map(|a| a.get_b().clone())
+            .chain(blocks.iter().flat_map(|b| b.get_input_b().to_vec()))
+            .collect();
+        let carry_in = adders[0].get_carry_in().clone();
+        let sum: Vec<Rc<RefCell<Pin>>> = adders
+            .iter()
+            .map(|a| a.get_sum().clone())
+            .chain(blocks.iter().flat_map(|b| b.get_sum().to_vec()))
+            .collect();
+        let overflow = match blocks.last() {
+            Some(last) => last.get_carry().clone(),
+            None => adders.last().unwrap().get_carry().clone(),
+        };
+
+        zip(adders.iter(), adders[1..].iter()).for_each(|(a0, a1)| {
+            Pin::connect(a0.get_carry(), a1.get_carry_in());
+        });
+        let mut carry = adders.last().unwrap().get_carry();
+        for select_block in blocks.iter() {
+            Pin::connect(carry, select_block.get_carry_in());
+            carry = select_block.get_carry();
+        }
+
+        Self {
+            strong_false,
+            strong_true,
+            adders,
+            blocks,
+            input_a,
+            input_b,
+            carry_in,
+            sum,
+            overflow,
+        }
+    }
+}
+
+/// A block of a `CarrySelectAdder` after the first. It adds its inputs with both possible carries,
+/// and `carry_in` selects which sum and carry reach the outputs.
+#[derive(Device)]
+struct SelectBlock {
+    #[children]
+    adders_false: Vec<FullAdder>,
+    #[children]
+    adders_true: Vec<FullAdder>,
+    #[child]
+    not_gate: NotGate,
+    #[children]
+    select_false_gates: Vec<AndGate>,
+    #[children]
+    select_true_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    carry_in: Rc<RefCell<Pin>>,
+    #[pins]
+    sum: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    carry: Rc<RefCell<Pin>>,
+}
+
+impl SelectBlock {
+    /// Creates a new block of the desired width. The selection gates for bit `b` are at index `b`,
+    /// and those for the carry are at index `width`.
+    fn new(width: usize, strong_false: &Constant, strong_true: &Constant) -> Self {
+        let adders_false: Vec<FullAdder> = (0..width).map(|_| FullAdder::new()).collect();
+        let adders_true: Vec<FullAdder> = (0..width).map(|_| FullAdder::new()).collect();
+        let not_gate = NotGate::new();
+        let select_false_gates: Vec<AndGate> = (0..=width).map(|_| AndGate::new(2)).collect();
+        let select_true_gates: Vec<AndGate> = (0..=width).map(|_| AndGate::new(2)).collect();
+        let or_gates: Vec<OrGate> = (0..=width).map(|_| OrGate::new(2)).collect();
+        let input_a: Vec<Rc<RefCell<Pin>>> =
+            adders_false.iter().map(|a| a.get_a().clone()).collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> =
+            adders_false.iter().map(|a| a.get_b().clone()).collect();
+        let carry_in = not_gate.get_input().clone();
+        let sum: Vec<Rc<RefCell<Pin>>> = or_gates[..width]
+            .iter()
+            .map(|g| g.get_output().clone())
+            .collect();
+        let carry = or_gates[width].get_output().clone();
+
+        for chain in [&adders_false, &adders_true] {
+            zip(chain.iter(), chain[1..].iter()).for_each(|(a0, a1)| {
+                Pin::connect(a0.get_carry(), a1.get_carry_in());
+            });
+        }
+        Pin::connect(strong_false.get_output(), adders_false[0].get_carry_in());
+        Pin::connect(strong_true.get_output(), adders_true[0].get_carry_in());
+        for (adder_false, adder_true) in zip(adders_false.iter(), adders_true.iter()) {
+            Pin::connect(adder_false.get_a(), adder_true.get_a());
+            Pin::connect(adder_false.get_b(), adder_true.get_b());
+        }
+
+        // Each output is (result_false AND NOT carry_in) OR (result_true AND carry_in).
+        let results_false = adders_false
+            .iter()
+            .map(|a| a.get_sum())
+            .chain([adders_false.last().unwrap().get_carry()]);
+        let results_true = adders_true
+            .iter()
+            .map(|a| a.get_sum())
+            .chain([adders_true.last().unwrap().get_carry()]);
+        for (index, (result_false, result_true)) in zip(results_false, results_true).enumerate() {
+            let select_false_gate = &select_false_gates[index];
+            let select_true_gate = &select_true_gates[index];
+            Pin::connect(result_false, &select_false_gate.get_input()[0]);
+            Pin::connect(not_gate.get_output(), &select_false_gate.get_input()[1]);
+            Pin::connect(result_true, &select_true_gate.get_input()[0]);
+            Pin::connect(&carry_in, &select_true_gate.get_input()[1]);
+            Pin::connect(
+                select_false_gate.get_output(),
+                &or_gates[index].get_input()[0],
+            );
+            Pin::connect(
+                select_true_gate.get_output(),
+                &or_gates[index].get_input()[1],
+            );
+        }
+
+        Self {
+            adders_false,
+            adders_true,
+            not_gate,
+            select_false_gates,
+            select_true_gates,
+            or_gates,
+            input_a,
+            input_b,
+            carry_in,
+            sum,
+            carry,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CarryLookaheadAdder, RippleCarryAdder};
+    use foundation::{
+        check_equivalence, check_integer_truth_table, EquivalenceOptions, TimingAnalysis,
+    };
+
+    #[test]
+    fn test_carry_select_adder_logic() {
+        for (width, block) in [(1, 1), (1, 2), (2, 1), (3, 2), (4, 2), (5, 2), (5, 4)] {
+            test_carry_select_adder_n_bit(width, block);
+        }
+    }
+
+    // The carry only passes through the selection gates of each block after the first, so the
+    // critical path grows more slowly with the width than that of a ripple-carry adder.
+    #[test]
+    fn test_carry_select_adder_critical_path() {
+        for width in [8, 16] {
+            let select = TimingAnalysis::new(&CarrySelectAdder::new(width, 4));
+            let ripple = TimingAnalysis::new(&RippleCarryAdder::new(width));
+            let select_stages = select.get_critical_path().unwrap().get_stages();
+            let ripple_stages = ripple.get_critical_path().unwrap().get_stages();
+            assert!(select_stages < ripple_stages);
+        }
+    }
+
+    // Too wide to test every input, so it's checked against a carry-lookahead adder with random
+    // inputs instead.
+    #[test]
+    fn test_carry_select_adder_equivalence() {
+        let options = EquivalenceOptions {
+            random_vectors: 200,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_equivalence(
+                CarryLookaheadAdder::new(12),
+                CarrySelectAdder::new(12, 5),
+                &["input_a", "input_b", "carry_in"],
+                &options,
+            ),
+            Ok(200)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_carry_select_adder_width() {
+        CarrySelectAdder::new(0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_carry_select_adder_block() {
+        CarrySelectAdder::new(1, 0);
+    }
+
+    // Checks every row of the truth table of a carry-select adder with the given width and block
+    // size, including widths that aren't a multiple of the block size.
+    fn test_carry_select_adder_n_bit(width: usize, block: usize) {
+        let adder = CarrySelectAdder::new(width, block);
+        assert_eq!(adder.get_input_a().len(), width);
+        assert_eq!(adder.get_input_b().len(), width);
+        assert_eq!(adder.get_sum().len(), width);
+
+        let max_value = 1 << width;
+        let mismatches = check_integer_truth_table(
+            adder,
+            &["input_a", "input_b", "carry_in"],
+            &["sum", "overflow"],
+            |values| {
+                let total = values[0] + values[1] + values[2];
+                vec![total % max_value, (total >= max_value) as usize]
+            },
+        );
+        assert_eq!(mismatches, []);
+    }
+}
@@ -1,7 +1,13 @@
+mod adder_subtractor;
+mod carry_lookahead_adder;
+mod carry_select_adder;
 mod full_adder;
 mod half_adder;
 mod ripple_carry_adder;
 
+pub use adder_subtractor::AdderSubtractor;
+pub use carry_lookahead_adder::CarryLookaheadAdder;
+pub use carry_select_adder::CarrySelectAdder;
 pub use full_adder::FullAdder;
 pub use half_adder::HalfAdder;
 pub use ripple_carry_adder::RippleCarryAdder;
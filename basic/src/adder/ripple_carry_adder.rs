@@ -56,7 +56,10 @@ impl RippleCarryAdder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use foundation::{settle, DriveValue, LogicValue as LV, Pin, TestPin, DRIVE_VALUES};
+    use foundation::{
+        check_integer_truth_table, check_truth_table, DriveValue, LogicValue as LV, TimingAnalysis,
+        TruthTableValues,
+    };
 
     // This is a low-level tests that tests error and high impedance conditions as well. Because of
     // this, the truth table is quite large.
@@ -210,38 +213,17 @@ mod tests {
             _ => (LV::Error, LV::Error, LV::Error),
         };
 
-        let mut adder = RippleCarryAdder::new(2);
-        let mut test_pin_a0 = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_a1 = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_b0 = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_b1 = TestPin::new(DriveValue::HighImpedance);
-
-        Pin::connect(test_pin_a0.get_output(), &adder.get_input_a()[0]);
-        Pin::connect(test_pin_a1.get_output(), &adder.get_input_a()[1]);
-        Pin::connect(test_pin_b0.get_output(), &adder.get_input_b()[0]);
-        Pin::connect(test_pin_b1.get_output(), &adder.get_input_b()[1]);
-
-        for a0 in DRIVE_VALUES.iter() {
-            for a1 in DRIVE_VALUES.iter() {
-                for b0 in DRIVE_VALUES.iter() {
-                    for b1 in DRIVE_VALUES.iter() {
-                        test_pin_a0.set_drive(*a0);
-                        test_pin_a1.set_drive(*a1);
-                        test_pin_b0.set_drive(*b0);
-                        test_pin_b1.set_drive(*b1);
-                        settle(&mut adder);
-                        let actual_s0 = adder.get_sum()[0].borrow().read();
-                        let actual_s1 = adder.get_sum()[1].borrow().read();
-                        let actual_overflow = adder.get_overflow().borrow().read();
-                        let (expected_s1, expected_s0, expected_overflow) =
-                            expected(a0, a1, b0, b1);
-                        assert_eq!(actual_s0, expected_s0);
-                        assert_eq!(actual_s1, expected_s1);
-                        assert_eq!(actual_overflow, expected_overflow);
-                    }
-                }
-            }
-        }
+        let mismatches = check_truth_table(
+            RippleCarryAdder::new(2),
+            &["input_a", "input_b"],
+            &["sum", "overflow"],
+            TruthTableValues::DriveValues,
+            |drives| {
+                let (s1, s0, overflow) = expected(&drives[0], &drives[1], &drives[2], &drives[3]);
+                vec![s0, s1, overflow]
+            },
+        );
+        assert_eq!(mismatches, []);
     }
 
     // This is a high-level tests that tests that the logic in the adders is actually equivalent to
@@ -254,6 +236,24 @@ mod tests {
         test_ripple_carry_adder_n_bit(4);
     }
 
+    // The carry ripples through every adder, so the critical path gets longer with the width.
+    #[test]
+    fn test_ripple_carry_adder_critical_path() {
+        for width in 1..=4 {
+            let analysis = TimingAnalysis::new(&RippleCarryAdder::new(width));
+            let critical_path = analysis.get_critical_path().unwrap();
+            assert_eq!(critical_path.get_input(), "input_a[0]");
+            assert_eq!(critical_path.get_output(), "overflow");
+            assert_eq!(critical_path.get_stages(), 8 + 6 * (width - 1));
+            assert!(critical_path.get_transistors()[0].starts_with("adders[0]."));
+            assert!(critical_path
+                .get_transistors()
+                .last()
+                .unwrap()
+                .starts_with(&format!("adders[{}].", width - 1)));
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_bad_ripple_carry_adder() {
@@ -263,50 +263,16 @@ mod tests {
     // Utility function that fully tests the truth table (not including error conditions) for an
     // n-bit ripple-carry adder.
     fn test_ripple_carry_adder_n_bit(width: usize) {
-        let max_value = 2usize.pow(width as u32);
-        let set_pins = |pins: &mut Vec<TestPin>, value: usize| {
-            for (index, pin) in pins.iter_mut().enumerate() {
-                pin.set_drive(DriveValue::Strong(
-                    value / 2usize.pow(index as u32) % 2 == 1,
-                ));
-            }
-        };
-        let read_pins = |pins: &Vec<Rc<RefCell<Pin>>>| {
-            let mut sum = 0;
-            for (index, pin) in pins.iter().enumerate() {
-                if pin.borrow().read() == LV::Driven(true) {
-                    sum += 2usize.pow(index as u32);
-                }
-            }
-            sum
-        };
-
-        let mut adder = RippleCarryAdder::new(width);
-
-        let mut test_pins_a: Vec<TestPin> = (0..width)
-            .map(|_| TestPin::new(DriveValue::HighImpedance))
-            .collect();
-        for (test_pin, input_pin) in zip(test_pins_a.iter(), adder.get_input_a().iter()) {
-            Pin::connect(test_pin.get_output(), input_pin);
-        }
-
-        let mut test_pins_b: Vec<TestPin> = (0..width)
-            .map(|_| TestPin::new(DriveValue::HighImpedance))
-            .collect();
-        for (test_pin, input_pin) in zip(test_pins_b.iter(), adder.get_input_b().iter()) {
-            Pin::connect(test_pin.get_output(), input_pin);
-        }
-
-        for value_a in 0..max_value {
-            for value_b in 0..max_value {
-                set_pins(&mut test_pins_a, value_a);
-                set_pins(&mut test_pins_b, value_b);
-                settle(&mut adder);
-                let actual_sum = read_pins(adder.get_sum());
-                let actual_overflow = adder.get_overflow().borrow().read();
-                assert_eq!(actual_sum, (value_a + value_b) % max_value);
-                assert_eq!(actual_overflow, LV::Driven(value_a + value_b >= max_value));
-            }
-        }
+        let max_value = 1 << width;
+        let mismatches = check_integer_truth_table(
+            RippleCarryAdder::new(width),
+            &["input_a", "input_b"],
+            &["sum", "overflow"],
+            |values| {
+                let total = values[0] + values[1];
+                vec![total % max_value, (total >= max_value) as usize]
+            },
+        );
+        assert_eq!(mismatches, []);
     }
 }
@@ -0,0 +1,336 @@
+use crate::{AdderSubtractor, Multiplexer};
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NorGate, NotGate, OrGate, XorGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The number of bits in the opcode of an `Alu`.
+const OPCODE_BITS: usize = 4;
+
+/// The operations an `Alu` can perform. The value of each is the unsigned integer to put on the
+/// `opcode` pins to select it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AluOperation {
+    /// `input_a + input_b`.
+    Add = 0,
+    /// `input_a - input_b`.
+    Sub = 1,
+    /// `input_a & input_b`.
+    And = 2,
+    /// `input_a | input_b`.
+    Or = 3,
+    /// `input_a ^ input_b`.
+    Xor = 4,
+    /// `!input_a`.
+    Not = 5,
+    /// `input_a << 1`.
+    Shl1 = 6,
+    /// `input_a >> 1`, shifting in zero.
+    Shr1 = 7,
+    /// `input_a`.
+    Pass = 8,
+}
+
+/// An arithmetic logic unit, which performs one of the `AluOperation`s on n-bit inputs, selected
+/// by the unsigned integer on `opcode`. Opcodes that aren't an operation give zero.
+///
+/// `zero` is high if `output` is zero, and `negative` is the highest bit of `output`. For `Add` and
+/// `Sub`, `carry` and `overflow` are the `carry_out` and `signed_overflow` of an
+/// `AdderSubtractor`, so for `Sub` `carry` is high when there's no borrow. For `Shl1` and `Shr1`,
+/// `carry` is the bit shifted out. Otherwise they're low.
+///
+/// Every operation is computed all the time, and a `Multiplexer` selects the result. Another
+/// `Multiplexer` selects the carry and overflow flags.
+#[derive(Device)]
+pub struct Alu {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    adder_subtractor: AdderSubtractor,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[children]
+    xor_gates: Vec<XorGate>,
+    #[children]
+    not_gates: Vec<NotGate>,
+    #[child]
+    result_multiplexer: Multiplexer,
+    #[child]
+    flag_multiplexer: Multiplexer,
+    #[child]
+    zero_gate: NorGate,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    opcode: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    zero: Rc<RefCell<Pin>>,
+    #[pin]
+    negative: Rc<RefCell<Pin>>,
+    #[pin]
+    carry: Rc<RefCell<Pin>>,
+    #[pin]
+    overflow: Rc<RefCell<Pin>>,
+}
+
+impl Alu {
+    /// Creates a new `Alu` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("Alu width must be non-zero.")
+        }
+
+        let strong_false = Constant::new_strong(false);
+        let adder_subtractor = AdderSubtractor::new(width);
+        let and_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(2)).collect();
+        let xor_gates: Vec<XorGate> = (0..width).map(|_| XorGate::new()).collect();
+        let not_gates: Vec<NotGate> = (0..width).map(|_| NotGate::new()).collect();
+        let result_multiplexer = Multiplexer::new(OPCODE_BITS, width);
+        let flag_multiplexer = Multiplexer::new(OPCODE_BITS, 2);
+        let zero_gate = NorGate::new(width.max(2));
+        let input_a = adder_subtractor.get_input_a().to_vec();
+        let input_b = adder_subtractor.get_input_b().to_vec();
+        let opcode = result_multiplexer.get_select().to_vec();
+        let output = result_multiplexer.get_output().to_vec();
+        let zero = zero_gate.get_output().clone();
+        let negative = output[width - 1].clone();
+        let carry = flag_multiplexer.get_output()[0].clone();
+        let overflow = flag_multiplexer.get_output()[1].clone();
+
+        for bit in 0..width {
+            Pin::connect(&input_a[bit], &and_gates[bit].get_input()[0]);
+            Pin::connect(&input_b[bit], &and_gates[bit].get_input()[1]);
+            Pin::connect(&input_a[bit], &or_gates[bit].get_input()[0]);
+            Pin::connect(&input_b[bit], &or_gates[bit].get_input()[1]);
+            Pin::connect(&input_a[bit], xor_gates[bit].get_a_input());
+            Pin::connect(&input_b[bit], xor_gates[bit].get_b_input());
+            Pin::connect(&input_a[bit], not_gates[bit].get_input());
+        }
+
+        for (opcode_pin, flag_select_pin) in opcode.iter().zip(flag_multiplexer.get_select()) {
+            Pin::connect(opcode_pin, flag_select_pin);
+        }
+
+        // The adder subtracts for odd opcodes, which only matters for `Sub`.
+        Pin::connect(&opcode[0], adder_subtractor.get_subtract());
+
+        let false_pin = strong_false.get_output();
+        let results: Vec<(AluOperation, Vec<&Rc<RefCell<Pin>>>)> = vec![
+            (
+                AluOperation::Add,
+                adder_subtractor.get_sum().iter().collect(),
+            ),
+            (
+                AluOperation::Sub,
+                adder_subtractor.get_sum().iter().collect(),
+            ),
+            (
+                AluOperation::And,
+                and_gates.iter().map(|g| g.get_output()).collect(),
+            ),
+            (
+                AluOperation::Or,
+                or_gates.iter().map(|g| g.get_output()).collect(),
+            ),
+            (
+                AluOperation::Xor,
+                xor_gates.iter().map(|g| g.get_output()).collect(),
+            ),
+            (
+                AluOperation::Not,
+                not_gates.iter().map(|g| g.get_output()).collect(),
+            ),
+            (
+                AluOperation::Shl1,
+                [false_pin]
+                    .into_iter()
+                    .chain(&input_a[..width - 1])
+                    .collect(),
+            ),
+            (
+                AluOperation::Shr1,
+                input_a[1..].iter().chain([false_pin]).collect(),
+            ),
+            (AluOperation::Pass, input_a.iter().collect()),
+        ];
+        let carry_out = adder_subtractor.get_carry_out();
+        let signed_overflow = adder_subtractor.get_signed_overflow();
+        let flags: Vec<(AluOperation, [&Rc<RefCell<Pin>>; 2])> = vec![
+            (AluOperation::Add, [carry_out, signed_overflow]),
+            (AluOperation::Sub, [carry_out, signed_overflow]),
+            (AluOperation::Shl1, [&input_a[width - 1], false_pin]),
+            (AluOperation::Shr1, [&input_a[0], false_pin]),
+        ];
+
+        // Any multiplexer input that isn't the result or flags of an operation is tied low.
+        for index in 0..1 << OPCODE_BITS {
+            let result = results
+                .iter()
+                .find(|(operation, _)| *operation as usize == index)
+                .map(|(_, result)| result.clone())
+                .unwrap_or_else(|| vec![false_pin; width]);
+            for (bit, pin) in result.iter().enumerate() {
+                Pin::connect(pin, &result_multiplexer.get_input()[index * width + bit]);
+            }
+            let flag_pins = flags
+                .iter()
+                .find(|(operation, _)| *operation as usize == index)
+                .map_or([false_pin; 2], |(_, flag_pins)| *flag_pins);
+            for (bit, pin) in flag_pins.iter().enumerate() {
+                Pin::connect(pin, &flag_multiplexer.get_input()[index * 2 + bit]);
+            }
+        }
+
+        // A NOR gate needs at least two inputs, so with a single bit the spare input is tied low.
+        for (bit, zero_input) in zero_gate.get_input().iter().enumerate() {
+            Pin::connect(output.get(bit).unwrap_or(false_pin), zero_input);
+        }
+
+        Self {
+            strong_false,
+            adder_subtractor,
+            and_gates,
+            or_gates,
+            xor_gates,
+            not_gates,
+            result_multiplexer,
+            flag_multiplexer,
+            zero_gate,
+            input_a,
+            input_b,
+            opcode,
+            output,
+            zero,
+            negative,
+            carry,
+            overflow,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, settle, DriveValue, LogicValue, TestBus};
+
+    const OPERATIONS: [AluOperation; 9] = [
+        AluOperation::Add,
+        AluOperation::Sub,
+        AluOperation::And,
+        AluOperation::Or,
+        AluOperation::Xor,
+        AluOperation::Not,
+        AluOperation::Shl1,
+        AluOperation::Shr1,
+        AluOperation::Pass,
+    ];
+
+    // Returns the expected output, carry and overflow of an operation.
+    fn expected(operation: AluOperation, width: usize, a: usize, b: usize) -> (usize, bool, bool) {
+        let mask = (1 << width) - 1;
+        let sign = |value: usize| value >> (width - 1) & 1 == 1;
+        match operation {
+            AluOperation::Add => {
+                let output = (a + b) & mask;
+                let overflow = sign(a) == sign(b) && sign(output) != sign(a);
+                (output, a + b > mask, overflow)
+            }
+            AluOperation::Sub => {
+                let output = a.wrapping_sub(b) & mask;
+                let overflow = sign(a) != sign(b) && sign(output) != sign(a);
+                (output, a >= b, overflow)
+            }
+            AluOperation::And => (a & b, false, false),
+            AluOperation::Or => (a | b, false, false),
+            AluOperation::Xor => (a ^ b, false, false),
+            AluOperation::Not => (!a & mask, false, false),
+            AluOperation::Shl1 => ((a << 1) & mask, sign(a), false),
+            AluOperation::Shr1 => (a >> 1, a & 1 == 1, false),
+            AluOperation::Pass => (a, false, false),
+        }
+    }
+
+    #[test]
+    fn test_alu() {
+        for width in 1..=3 {
+            let mut alu = Alu::new(width);
+            let mut test_bus_a = TestBus::new(alu.get_input_a(), DriveValue::HighImpedance);
+            let mut test_bus_b = TestBus::new(alu.get_input_b(), DriveValue::HighImpedance);
+            let mut test_bus_opcode = TestBus::new(alu.get_opcode(), DriveValue::HighImpedance);
+
+            for operation in OPERATIONS {
+                test_bus_opcode.set_value(operation as usize);
+                for a in 0..1 << width {
+                    for b in 0..1 << width {
+                        test_bus_a.set_value(a);
+                        test_bus_b.set_value(b);
+                        settle(&mut alu);
+                        let (output, carry, overflow) = expected(operation, width, a, b);
+                        let message = format!("{:?} {} {} width {}", operation, a, b, width);
+                        assert_eq!(read_bus(alu.get_output()), Some(output), "{}", message);
+                        assert_eq!(
+                            alu.get_zero().borrow().read(),
+                            LogicValue::Driven(output == 0),
+                            "{}",
+                            message
+                        );
+                        assert_eq!(
+                            alu.get_negative().borrow().read(),
+                            LogicValue::Driven(output >> (width - 1) == 1),
+                            "{}",
+                            message
+                        );
+                        assert_eq!(
+                            alu.get_carry().borrow().read(),
+                            LogicValue::Driven(carry),
+                            "{}",
+                            message
+                        );
+                        assert_eq!(
+                            alu.get_overflow().borrow().read(),
+                            LogicValue::Driven(overflow),
+                            "{}",
+                            message
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // Opcodes that aren't an operation give zero with every flag low except `zero`.
+    #[test]
+    fn test_alu_unused_opcodes() {
+        let mut alu = Alu::new(2);
+        let mut test_bus_a = TestBus::new(alu.get_input_a(), DriveValue::HighImpedance);
+        let mut test_bus_b = TestBus::new(alu.get_input_b(), DriveValue::HighImpedance);
+        let mut test_bus_opcode = TestBus::new(alu.get_opcode(), DriveValue::HighImpedance);
+        test_bus_a.set_value(3);
+        test_bus_b.set_value(3);
+        for opcode in OPERATIONS.len()..1 << OPCODE_BITS {
+            test_bus_opcode.set_value(opcode);
+            settle(&mut alu);
+            assert_eq!(read_bus(alu.get_output()), Some(0));
+            assert_eq!(alu.get_zero().borrow().read(), LogicValue::Driven(true));
+            assert_eq!(alu.get_carry().borrow().read(), LogicValue::Driven(false));
+            assert_eq!(
+                alu.get_overflow().borrow().read(),
+                LogicValue::Driven(false)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_alu() {
+        Alu::new(0);
+    }
+}
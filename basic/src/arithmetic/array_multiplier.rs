@@ -0,0 +1,127 @@
+use crate::FullAdder;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::AndGate;
+use std::cell::RefCell;
+use std::iter::zip;
+use std::rc::Rc;
+
+/// A device that multiplies two n-bit unsigned integers, giving a 2n-bit product, so it never
+/// overflows.
+///
+/// An AND gate for each pair of bits forms the partial products, i.e. `input_a` ANDed with each bit
+/// of `input_b`. Each row of full adders after the first adds the next partial product to the
+/// running sum, shifted one place further left. The lowest bit of each row's sum is a bit of the
+/// product, and the rest go on to the next row along with the row's carry out.
+#[derive(Device)]
+pub struct ArrayMultiplier {
+    #[child]
+    strong_false: Constant,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[children]
+    adders: Vec<FullAdder>,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    product: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl ArrayMultiplier {
+    /// Creates a new `ArrayMultiplier` of the desired width. The AND gate for bit `a` of `input_a`
+    /// and bit `b` of `input_b` is at index `b * width + a`, and the adder for bit `a` of the row
+    /// which adds the partial product for bit `b` is at index `(b - 1) * width + a`.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("ArrayMultiplier width must be non-zero.")
+        }
+
+        let strong_false = Constant::new_strong(false);
+        let and_gates: Vec<AndGate> = (0..width * width).map(|_| AndGate::new(2)).collect();
+        let adders: Vec<FullAdder> = (0..(width - 1) * width).map(|_| FullAdder::new()).collect();
+        let input_a: Vec<Rc<RefCell<Pin>>> = and_gates[..width]
+            .iter()
+            .map(|g| g.get_input()[0].clone())
+            .collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> = (0..width)
+            .map(|b| and_gates[b * width].get_input()[1].clone())
+            .collect();
+
+        for b in 0..width {
+            for a in 0..width {
+                let and_gate = &and_gates[b * width + a];
+                Pin::connect(&input_a[a], &and_gate.get_input()[0]);
+                Pin::connect(&input_b[b], &and_gate.get_input()[1]);
+            }
+        }
+
+        // The running sum starts as the first partial product, with a zero shifted in at the top.
+        let mut product = vec![and_gates[0].get_output().clone()];
+        let mut running: Vec<Rc<RefCell<Pin>>> = and_gates[1..width]
+            .iter()
+            .map(|g| g.get_output().clone())
+            .chain([strong_false.get_output().clone()])
+            .collect();
+        for b in 1..width {
+            let row = &adders[(b - 1) * width..b * width];
+            Pin::connect(strong_false.get_output(), row[0].get_carry_in());
+            zip(row.iter(), row[1..].iter()).for_each(|(a0, a1)| {
+                Pin::connect(a0.get_carry(), a1.get_carry_in());
+            });
+            for (a, adder) in row.iter().enumerate() {
+                Pin::connect(&running[a], adder.get_a());
+                Pin::connect(and_gates[b * width + a].get_output(), adder.get_b());
+            }
+            product.push(row[0].get_sum().clone());
+            running = row[1..]
+                .iter()
+                .map(|a| a.get_sum().clone())
+                .chain([row[width - 1].get_carry().clone()])
+                .collect();
+        }
+        product.extend(running);
+
+        Self {
+            strong_false,
+            and_gates,
+            adders,
+            input_a,
+            input_b,
+            product,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, Simulator, TestBus};
+
+    #[test]
+    fn test_array_multiplier() {
+        for width in 1..=4 {
+            let multiplier = ArrayMultiplier::new(width);
+            assert_eq!(multiplier.get_product().len(), 2 * width);
+            let mut test_bus_a = TestBus::new(multiplier.get_input_a(), DriveValue::HighImpedance);
+            let mut test_bus_b = TestBus::new(multiplier.get_input_b(), DriveValue::HighImpedance);
+            let mut simulator = Simulator::new(&multiplier);
+
+            for a in 0..1 << width {
+                for b in 0..1 << width {
+                    test_bus_a.set_value(a);
+                    test_bus_b.set_value(b);
+                    simulator.settle();
+                    assert_eq!(read_bus(multiplier.get_product()), Some(a * b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_array_multiplier() {
+        ArrayMultiplier::new(0);
+    }
+}
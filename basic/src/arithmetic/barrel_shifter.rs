@@ -0,0 +1,212 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A device that shifts an n-bit input by a variable amount in a single step. `amount` is an
+/// unsigned integer with enough bits to shift by up to n - 1, and at least one.
+///
+/// While `right` is low the input is shifted left, shifting in zeros. While `right` is high it's
+/// shifted right, shifting in zeros if `arithmetic` is low (a logical shift) or copies of the
+/// highest bit if it's high (an arithmetic shift). `arithmetic` is ignored for left shifts.
+/// Shifting by n or more, which is possible when n isn't a power of two, shifts out every bit.
+///
+/// There's a stage for each bit of `amount`, which shifts by that bit's place value if it's high.
+/// Each bit of each stage takes either the same bit from the previous stage or the bit to its left
+/// or right, depending on `right`, using three AND gates and an OR gate.
+#[derive(Device)]
+pub struct BarrelShifter {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    not_right_gate: NotGate,
+    #[child]
+    fill_gate: AndGate,
+    #[children]
+    not_amount_gates: Vec<NotGate>,
+    #[children]
+    hold_gates: Vec<AndGate>,
+    #[children]
+    left_gates: Vec<AndGate>,
+    #[children]
+    right_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    amount: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    right: Rc<RefCell<Pin>>,
+    #[pin]
+    arithmetic: Rc<RefCell<Pin>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl BarrelShifter {
+    /// Creates a new `BarrelShifter` of the desired width. The gates for stage `s` and bit `b` are
+    /// at index `s * width + b`.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("BarrelShifter width must be non-zero.")
+        }
+
+        let amount_bits = (usize::BITS - (width - 1).leading_zeros()).max(1) as usize;
+        let gates = amount_bits * width;
+        let strong_false = Constant::new_strong(false);
+        let not_right_gate = NotGate::new();
+        let fill_gate = AndGate::new(2);
+        let not_amount_gates: Vec<NotGate> = (0..amount_bits).map(|_| NotGate::new()).collect();
+        let hold_gates: Vec<AndGate> = (0..gates).map(|_| AndGate::new(2)).collect();
+        let left_gates: Vec<AndGate> = (0..gates).map(|_| AndGate::new(3)).collect();
+        let right_gates: Vec<AndGate> = (0..gates).map(|_| AndGate::new(3)).collect();
+        let or_gates: Vec<OrGate> = (0..gates).map(|_| OrGate::new(3)).collect();
+        let input: Vec<Rc<RefCell<Pin>>> = hold_gates[..width]
+            .iter()
+            .map(|g| g.get_input()[1].clone())
+            .collect();
+        let amount: Vec<Rc<RefCell<Pin>>> = not_amount_gates
+            .iter()
+            .map(|g| g.get_input().clone())
+            .collect();
+        let right = not_right_gate.get_input().clone();
+        let arithmetic = fill_gate.get_input()[0].clone();
+        let output: Vec<Rc<RefCell<Pin>>> = or_gates[gates - width..]
+            .iter()
+            .map(|g| g.get_output().clone())
+            .collect();
+
+        // The bit shifted in on the left by a right shift is the highest bit of the input, if the
+        // shift is arithmetic.
+        Pin::connect(&input[width - 1], &fill_gate.get_input()[1]);
+
+        for stage in 0..amount_bits {
+            let distance = 1 << stage;
+            let previous: Vec<Rc<RefCell<Pin>>> = match stage {
+                0 => input.clone(),
+                _ => or_gates[(stage - 1) * width..stage * width]
+                    .iter()
+                    .map(|g| g.get_output().clone())
+                    .collect(),
+            };
+            let shift = &amount[stage];
+            let hold = not_amount_gates[stage].get_output();
+            for bit in 0..width {
+                let index = stage * width + bit;
+                let from_left = match bit.checked_sub(distance) {
+                    Some(source) => &previous[source],
+                    None => strong_false.get_output(),
+                };
+                let from_right = previous
+                    .get(bit + distance)
+                    .unwrap_or(fill_gate.get_output());
+
+                Pin::connect(hold, &hold_gates[index].get_input()[0]);
+                Pin::connect(&previous[bit], &hold_gates[index].get_input()[1]);
+                Pin::connect(shift, &left_gates[index].get_input()[0]);
+                Pin::connect(
+                    not_right_gate.get_output(),
+                    &left_gates[index].get_input()[1],
+                );
+                Pin::connect(from_left, &left_gates[index].get_input()[2]);
+                Pin::connect(shift, &right_gates[index].get_input()[0]);
+                Pin::connect(&right, &right_gates[index].get_input()[1]);
+                Pin::connect(from_right, &right_gates[index].get_input()[2]);
+                Pin::connect(
+                    hold_gates[index].get_output(),
+                    &or_gates[index].get_input()[0],
+                );
+                Pin::connect(
+                    left_gates[index].get_output(),
+                    &or_gates[index].get_input()[1],
+                );
+                Pin::connect(
+                    right_gates[index].get_output(),
+                    &or_gates[index].get_input()[2],
+                );
+            }
+        }
+
+        Self {
+            strong_false,
+            not_right_gate,
+            fill_gate,
+            not_amount_gates,
+            hold_gates,
+            left_gates,
+            right_gates,
+            or_gates,
+            input,
+            amount,
+            right,
+            arithmetic,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, settle, DriveValue, TestBus, TestPin};
+
+    #[test]
+    fn test_barrel_shifter() {
+        for width in 1..=5 {
+            let mut shifter = BarrelShifter::new(width);
+            let amount_bits = shifter.get_amount().len();
+            let mask = (1 << width) - 1;
+            let mut test_bus_input = TestBus::new(shifter.get_input(), DriveValue::HighImpedance);
+            let mut test_bus_amount = TestBus::new(shifter.get_amount(), DriveValue::HighImpedance);
+            let mut test_pin_right = TestPin::new(DriveValue::HighImpedance);
+            let mut test_pin_arithmetic = TestPin::new(DriveValue::HighImpedance);
+            Pin::connect(test_pin_right.get_output(), shifter.get_right());
+            Pin::connect(test_pin_arithmetic.get_output(), shifter.get_arithmetic());
+
+            for (right, arithmetic) in [(false, false), (false, true), (true, false), (true, true)]
+            {
+                test_pin_right.set_drive(DriveValue::Strong(right));
+                test_pin_arithmetic.set_drive(DriveValue::Strong(arithmetic));
+                for value in 0..1 << width {
+                    test_bus_input.set_value(value);
+                    for amount in 0..1 << amount_bits {
+                        test_bus_amount.set_value(amount);
+                        settle(&mut shifter);
+
+                        // Sign extend the value to an i64 for arithmetic shifts.
+                        let signed = ((value as i64) << (64 - width)) >> (64 - width);
+                        let expected = match (right, arithmetic) {
+                            (false, _) => value.checked_shl(amount as u32).unwrap_or(0) & mask,
+                            (true, false) => value.checked_shr(amount as u32).unwrap_or(0),
+                            (true, true) => (signed >> amount.min(63)) as usize & mask,
+                        };
+                        assert_eq!(
+                            read_bus(shifter.get_output()),
+                            Some(expected),
+                            "value {} amount {} right {} arithmetic {}",
+                            value,
+                            amount,
+                            right,
+                            arithmetic
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_barrel_shifter_amount_bits() {
+        for (width, amount_bits) in [(1, 1), (2, 1), (3, 2), (4, 2), (5, 3), (32, 5)] {
+            assert_eq!(BarrelShifter::new(width).get_amount().len(), amount_bits);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_barrel_shifter() {
+        BarrelShifter::new(0);
+    }
+}
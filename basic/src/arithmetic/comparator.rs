@@ -0,0 +1,176 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate, XnorGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A device that compares two n-bit integers. `equal` is high if they're equal, `less_unsigned` is
+/// high if `input_a < input_b` when they're unsigned, and `less_signed` is high if
+/// `input_a < input_b` when they're two's complement.
+///
+/// An XNOR gate per bit checks whether the bits are equal. `input_a` is less than `input_b` if, at
+/// the highest bit where they differ, the bit of `input_a` is low. Each bit has an AND gate which
+/// checks for that, and an OR gate combines them. The signed comparison is the same except at the
+/// highest bit, where a high bit means negative, so it has its own AND gate which checks the
+/// opposite.
+#[derive(Device)]
+pub struct Comparator {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    strong_true: Constant,
+    #[children]
+    xnor_gates: Vec<XnorGate>,
+    #[children]
+    not_a_gates: Vec<NotGate>,
+    #[child]
+    not_b_gate: NotGate,
+    #[children]
+    less_gates: Vec<AndGate>,
+    #[child]
+    signed_less_gate: AndGate,
+    #[child]
+    equal_gate: AndGate,
+    #[child]
+    less_unsigned_gate: OrGate,
+    #[child]
+    less_signed_gate: OrGate,
+    #[pins]
+    input_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    input_b: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    equal: Rc<RefCell<Pin>>,
+    #[pin]
+    less_unsigned: Rc<RefCell<Pin>>,
+    #[pin]
+    less_signed: Rc<RefCell<Pin>>,
+}
+
+impl Comparator {
+    /// Creates a new `Comparator` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("Comparator width must be non-zero.")
+        }
+
+        let msb = width - 1;
+        let strong_false = Constant::new_strong(false);
+        let strong_true = Constant::new_strong(true);
+        let xnor_gates: Vec<XnorGate> = (0..width).map(|_| XnorGate::new()).collect();
+        let not_a_gates: Vec<NotGate> = (0..width).map(|_| NotGate::new()).collect();
+        let not_b_gate = NotGate::new();
+        let less_gates: Vec<AndGate> = (0..width)
+            .map(|bit| AndGate::new(width - bit + 1))
+            .collect();
+        let signed_less_gate = AndGate::new(2);
+        let equal_gate = AndGate::new(width.max(2));
+        let less_unsigned_gate = OrGate::new(width.max(2));
+        let less_signed_gate = OrGate::new(width.max(2));
+        let input_a: Vec<Rc<RefCell<Pin>>> =
+            xnor_gates.iter().map(|g| g.get_a_input().clone()).collect();
+        let input_b: Vec<Rc<RefCell<Pin>>> =
+            xnor_gates.iter().map(|g| g.get_b_input().clone()).collect();
+        let equal = equal_gate.get_output().clone();
+        let less_unsigned = less_unsigned_gate.get_output().clone();
+        let less_signed = less_signed_gate.get_output().clone();
+
+        for bit in 0..width {
+            Pin::connect(&input_a[bit], not_a_gates[bit].get_input());
+            Pin::connect(xnor_gates[bit].get_output(), &equal_gate.get_input()[bit]);
+
+            // Bit b of input_a is low and bit b of input_b is high, and every higher bit is equal.
+            let less_gate = &less_gates[bit];
+            Pin::connect(not_a_gates[bit].get_output(), &less_gate.get_input()[0]);
+            Pin::connect(&input_b[bit], &less_gate.get_input()[1]);
+            for (index, xnor_gate) in xnor_gates[bit + 1..].iter().enumerate() {
+                Pin::connect(xnor_gate.get_output(), &less_gate.get_input()[index + 2]);
+            }
+            Pin::connect(less_gate.get_output(), &less_unsigned_gate.get_input()[bit]);
+            if bit != msb {
+                Pin::connect(less_gate.get_output(), &less_signed_gate.get_input()[bit]);
+            }
+        }
+
+        // At the highest bit, input_a is less if it's negative and input_b isn't.
+        Pin::connect(&input_b[msb], not_b_gate.get_input());
+        Pin::connect(&input_a[msb], &signed_less_gate.get_input()[0]);
+        Pin::connect(not_b_gate.get_output(), &signed_less_gate.get_input()[1]);
+        Pin::connect(
+            signed_less_gate.get_output(),
+            &less_signed_gate.get_input()[msb],
+        );
+
+        // The gates need at least two inputs, so with a single bit the spare inputs are tied to
+        // values that don't affect the result.
+        for spare in equal_gate.get_input()[width..].iter() {
+            Pin::connect(strong_true.get_output(), spare);
+        }
+        for spare in less_unsigned_gate.get_input()[width..]
+            .iter()
+            .chain(&less_signed_gate.get_input()[width..])
+        {
+            Pin::connect(strong_false.get_output(), spare);
+        }
+
+        Self {
+            strong_false,
+            strong_true,
+            xnor_gates,
+            not_a_gates,
+            not_b_gate,
+            less_gates,
+            signed_less_gate,
+            equal_gate,
+            less_unsigned_gate,
+            less_signed_gate,
+            input_a,
+            input_b,
+            equal,
+            less_unsigned,
+            less_signed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, DriveValue, LogicValue, TestBus};
+
+    #[test]
+    fn test_comparator() {
+        for width in 1..=4 {
+            let mut comparator = Comparator::new(width);
+            let mut test_bus_a = TestBus::new(comparator.get_input_a(), DriveValue::HighImpedance);
+            let mut test_bus_b = TestBus::new(comparator.get_input_b(), DriveValue::HighImpedance);
+            let signed = |value: usize| ((value as i64) << (64 - width)) >> (64 - width);
+
+            for a in 0..1 << width {
+                for b in 0..1 << width {
+                    test_bus_a.set_value(a);
+                    test_bus_b.set_value(b);
+                    settle(&mut comparator);
+                    assert_eq!(
+                        comparator.get_equal().borrow().read(),
+                        LogicValue::Driven(a == b)
+                    );
+                    assert_eq!(
+                        comparator.get_less_unsigned().borrow().read(),
+                        LogicValue::Driven(a < b)
+                    );
+                    assert_eq!(
+                        comparator.get_less_signed().borrow().read(),
+                        LogicValue::Driven(signed(a) < signed(b))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_comparator() {
+        Comparator::new(0);
+    }
+}
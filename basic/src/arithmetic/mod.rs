@@ -0,0 +1,11 @@
+mod alu;
+mod array_multiplier;
+mod barrel_shifter;
+mod comparator;
+mod restoring_divider;
+
+pub use alu::{Alu, AluOperation};
+pub use array_multiplier::ArrayMultiplier;
+pub use barrel_shifter::BarrelShifter;
+pub use comparator::Comparator;
+pub use restoring_divider::RestoringDivider;
@@ -0,0 +1,249 @@
+use crate::{AdderSubtractor, DFlipFlop};
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A sequential device that divides two n-bit unsigned integers, using the restoring division
+/// algorithm one bit per clock cycle.
+///
+/// On a rising edge of `clock` while `start` is high, `dividend` is loaded into the quotient
+/// register and the remainder register is cleared. `dividend` and `divisor` must then be held, and
+/// `start` kept low, and after another n rising edges `quotient` and `remainder` hold the result.
+///
+/// On each of those edges the remainder and quotient registers are shifted left together as one
+/// 2n-bit register. The divisor is subtracted from the shifted remainder, and if that doesn't
+/// borrow the difference replaces the remainder and a one is shifted into the quotient, otherwise
+/// the remainder is restored, i.e. kept as it was, and a zero is shifted in. Like the RISC-V `divu`
+/// and `remu` instructions, dividing by zero gives a quotient with every bit set and a remainder
+/// equal to the dividend.
+#[derive(Device)]
+pub struct RestoringDivider {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    strong_true: Constant,
+    #[child]
+    subtractor: AdderSubtractor,
+    #[child]
+    not_start_gate: NotGate,
+    #[child]
+    not_carry_gate: NotGate,
+    #[children]
+    difference_gates: Vec<AndGate>,
+    #[children]
+    restore_gates: Vec<AndGate>,
+    #[children]
+    remainder_or_gates: Vec<OrGate>,
+    #[children]
+    load_gates: Vec<AndGate>,
+    #[children]
+    shift_gates: Vec<AndGate>,
+    #[children]
+    quotient_or_gates: Vec<OrGate>,
+    #[children]
+    remainder_flip_flops: Vec<DFlipFlop>,
+    #[children]
+    quotient_flip_flops: Vec<DFlipFlop>,
+    #[pins]
+    dividend: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    divisor: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    start: Rc<RefCell<Pin>>,
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+    #[pins]
+    quotient: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    remainder: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl RestoringDivider {
+    /// Creates a new `RestoringDivider` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("RestoringDivider width must be non-zero.")
+        }
+
+        let strong_false = Constant::new_strong(false);
+        let strong_true = Constant::new_strong(true);
+        // The shifted remainder can be up to twice the divisor, so it needs an extra bit.
+        let subtractor = AdderSubtractor::new(width + 1);
+        let not_start_gate = NotGate::new();
+        let not_carry_gate = NotGate::new();
+        let difference_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(3)).collect();
+        let restore_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(3)).collect();
+        let remainder_or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(2)).collect();
+        let load_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let shift_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let quotient_or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(2)).collect();
+        let remainder_flip_flops: Vec<DFlipFlop> = (0..width).map(|_| DFlipFlop::new()).collect();
+        let quotient_flip_flops: Vec<DFlipFlop> = (0..width).map(|_| DFlipFlop::new()).collect();
+        let dividend: Vec<Rc<RefCell<Pin>>> = load_gates
+            .iter()
+            .map(|g| g.get_input()[1].clone())
+            .collect();
+        let divisor = subtractor.get_input_b()[..width].to_vec();
+        let start = not_start_gate.get_input().clone();
+        let clock = quotient_flip_flops[0].get_clock().clone();
+        let quotient: Vec<Rc<RefCell<Pin>>> = quotient_flip_flops
+            .iter()
+            .map(|f| f.get_output().clone())
+            .collect();
+        let remainder: Vec<Rc<RefCell<Pin>>> = remainder_flip_flops
+            .iter()
+            .map(|f| f.get_output().clone())
+            .collect();
+
+        for flip_flop in remainder_flip_flops.iter().chain(&quotient_flip_flops) {
+            Pin::connect(&clock, flip_flop.get_clock());
+        }
+
+        // The shifted remainder is the remainder shifted left, with the top bit of the quotient
+        // shifted in, and the divisor is zero extended. Subtracting doesn't borrow if the carry out
+        // of the subtractor is high.
+        let shifted: Vec<Rc<RefCell<Pin>>> = [quotient[width - 1].clone()]
+            .into_iter()
+            .chain(remainder.iter().cloned())
+            .collect();
+        for (shifted_pin, input_pin) in shifted.iter().zip(subtractor.get_input_a()) {
+            Pin::connect(shifted_pin, input_pin);
+        }
+        Pin::connect(strong_true.get_output(), subtractor.get_subtract());
+        let carry = subtractor.get_carry_out();
+        Pin::connect(carry, not_carry_gate.get_input());
+        Pin::connect(strong_false.get_output(), &subtractor.get_input_b()[width]);
+
+        for bit in 0..width {
+            // The remainder is cleared on start, and otherwise takes the difference if there was
+            // no borrow, or the shifted remainder if there was.
+            let difference_gate = &difference_gates[bit];
+            Pin::connect(not_start_gate.get_output(), &difference_gate.get_input()[0]);
+            Pin::connect(carry, &difference_gate.get_input()[1]);
+            Pin::connect(&subtractor.get_sum()[bit], &difference_gate.get_input()[2]);
+            let restore_gate = &restore_gates[bit];
+            Pin::connect(not_start_gate.get_output(), &restore_gate.get_input()[0]);
+            Pin::connect(not_carry_gate.get_output(), &restore_gate.get_input()[1]);
+            Pin::connect(&shifted[bit], &restore_gate.get_input()[2]);
+            Pin::connect(
+                difference_gate.get_output(),
+                &remainder_or_gates[bit].get_input()[0],
+            );
+            Pin::connect(
+                restore_gate.get_output(),
+                &remainder_or_gates[bit].get_input()[1],
+            );
+            Pin::connect(
+                remainder_or_gates[bit].get_output(),
+                remainder_flip_flops[bit].get_data(),
+            );
+
+            // The quotient is loaded with the dividend on start, and otherwise shifted left with
+            // the carry shifted in.
+            let shifted_in = match bit {
+                0 => carry,
+                _ => &quotient[bit - 1],
+            };
+            Pin::connect(&start, &load_gates[bit].get_input()[0]);
+            Pin::connect(
+                not_start_gate.get_output(),
+                &shift_gates[bit].get_input()[0],
+            );
+            Pin::connect(shifted_in, &shift_gates[bit].get_input()[1]);
+            Pin::connect(
+                load_gates[bit].get_output(),
+                &quotient_or_gates[bit].get_input()[0],
+            );
+            Pin::connect(
+                shift_gates[bit].get_output(),
+                &quotient_or_gates[bit].get_input()[1],
+            );
+            Pin::connect(
+                quotient_or_gates[bit].get_output(),
+                quotient_flip_flops[bit].get_data(),
+            );
+        }
+
+        Self {
+            strong_false,
+            strong_true,
+            subtractor,
+            not_start_gate,
+            not_carry_gate,
+            difference_gates,
+            restore_gates,
+            remainder_or_gates,
+            load_gates,
+            shift_gates,
+            quotient_or_gates,
+            remainder_flip_flops,
+            quotient_flip_flops,
+            dividend,
+            divisor,
+            start,
+            clock,
+            quotient,
+            remainder,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, Simulator, TestBus, TestPin};
+
+    #[test]
+    fn test_restoring_divider() {
+        for width in 1..=4 {
+            let divider = RestoringDivider::new(width);
+            let mut test_bus_dividend =
+                TestBus::new(divider.get_dividend(), DriveValue::Strong(false));
+            let mut test_bus_divisor =
+                TestBus::new(divider.get_divisor(), DriveValue::Strong(false));
+            let mut test_pin_start = TestPin::new(DriveValue::Strong(false));
+            let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+            Pin::connect(test_pin_start.get_output(), divider.get_start());
+            Pin::connect(test_pin_clock.get_output(), divider.get_clock());
+            let mut simulator = Simulator::new(&divider);
+            simulator.settle();
+
+            // Settles any changes to the inputs before the rising edge, to meet the setup time.
+            let mut clock = |simulator: &mut Simulator| {
+                simulator.settle();
+                test_pin_clock.set_drive(DriveValue::Strong(true));
+                simulator.settle();
+                test_pin_clock.set_drive(DriveValue::Strong(false));
+                simulator.settle();
+            };
+
+            for dividend in 0..1 << width {
+                for divisor in 0..1 << width {
+                    test_bus_dividend.set_value(dividend);
+                    test_bus_divisor.set_value(divisor);
+                    test_pin_start.set_drive(DriveValue::Strong(true));
+                    clock(&mut simulator);
+                    assert_eq!(read_bus(divider.get_quotient()), Some(dividend));
+                    assert_eq!(read_bus(divider.get_remainder()), Some(0));
+
+                    test_pin_start.set_drive(DriveValue::Strong(false));
+                    (0..width).for_each(|_| clock(&mut simulator));
+                    let (quotient, remainder) = match divisor {
+                        0 => ((1 << width) - 1, dividend),
+                        _ => (dividend / divisor, dividend % divisor),
+                    };
+                    assert_eq!(read_bus(divider.get_quotient()), Some(quotient));
+                    assert_eq!(read_bus(divider.get_remainder()), Some(remainder));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_restoring_divider() {
+        RestoringDivider::new(0);
+    }
+}
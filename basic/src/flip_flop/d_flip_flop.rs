@@ -0,0 +1,244 @@
+use crate::DLatch;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::NotGate;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A rising edge triggered D flip-flop. When `clock` goes from low to high, `output` takes on the
+/// value of `data`, and holds it until the next rising edge.
+///
+/// It's made from two `DLatch`es in a master/slave arrangement. The master latch is enabled while
+/// `clock` is low, and the slave latch, which drives the outputs, is enabled while `clock` is high.
+///
+/// `data` must be stable for at least 2 ticks before the rising edge of `clock` (the setup time)
+/// and for at least 1 tick after it (the hold time). Otherwise either value may be stored, or the
+/// master latch may be left oscillating until it's next enabled, in which case `settle` never
+/// returns, so use `settle_with_limit` to check for that when the timing isn't known to be met.
+///
+/// The asynchronous `set` and `reset` inputs force both latches, and so the output, regardless of
+/// `clock`. Like those of a `DLatch` they're weakly pulled low, so they can be left unconnected,
+/// and the flip-flop powers up storing `false`.
+#[derive(Device)]
+pub struct DFlipFlop {
+    #[child]
+    not_gate: NotGate,
+
+    #[child]
+    master: DLatch,
+
+    #[child]
+    slave: DLatch,
+
+    #[pin]
+    data: Rc<RefCell<Pin>>,
+
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+
+    #[pin]
+    set: Rc<RefCell<Pin>>,
+
+    #[pin]
+    reset: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output_inverted: Rc<RefCell<Pin>>,
+}
+
+impl DFlipFlop {
+    /// Creates a new D flip-flop.
+    pub fn new() -> Self {
+        let not_gate = NotGate::new();
+        let master = DLatch::new();
+        let slave = DLatch::new();
+        let data = master.get_data().clone();
+        let clock = slave.get_enable().clone();
+        let set = master.get_set().clone();
+        let reset = master.get_reset().clone();
+        let output = slave.get_output().clone();
+        let output_inverted = slave.get_output_inverted().clone();
+
+        Pin::connect(&clock, not_gate.get_input());
+        Pin::connect(not_gate.get_output(), master.get_enable());
+        Pin::connect(master.get_output(), slave.get_data());
+        Pin::connect(&set, slave.get_set());
+        Pin::connect(&reset, slave.get_reset());
+
+        Self {
+            not_gate,
+            master,
+            slave,
+            data,
+            clock,
+            set,
+            reset,
+            output,
+            output_inverted,
+        }
+    }
+}
+
+impl Default for DFlipFlop {
+    fn default() -> Self {
+        DFlipFlop::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, settle_with_limit, tick, DriveValue, LogicValue, TestPin};
+
+    #[test]
+    fn test_d_flip_flop() {
+        let mut flip_flop = DFlipFlop::default();
+        let mut test_pin_data = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+
+        let check = |flip_flop: &DFlipFlop, value: bool| {
+            assert_eq!(
+                flip_flop.get_output().borrow().read(),
+                LogicValue::Driven(value)
+            );
+            assert_eq!(
+                flip_flop.get_output_inverted().borrow().read(),
+                LogicValue::Driven(!value)
+            );
+        };
+
+        Pin::connect(test_pin_data.get_output(), flip_flop.get_data());
+        Pin::connect(test_pin_clock.get_output(), flip_flop.get_clock());
+
+        settle(&mut flip_flop);
+        check(&flip_flop, false);
+
+        (0..3).for_each(|_| {
+            // Changes to the data are ignored while the clock is low.
+            test_pin_data.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            check(&flip_flop, false);
+
+            // The data is captured on the rising edge.
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            check(&flip_flop, true);
+
+            // Changes to the data are ignored while the clock is high, and on the falling edge.
+            test_pin_data.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            check(&flip_flop, true);
+
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            check(&flip_flop, true);
+
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            check(&flip_flop, false);
+
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            check(&flip_flop, false);
+        });
+    }
+
+    #[test]
+    fn test_d_flip_flop_set_reset() {
+        let mut flip_flop = DFlipFlop::default();
+        let test_pin_data = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_set = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_reset = TestPin::new(DriveValue::Strong(false));
+
+        Pin::connect(test_pin_data.get_output(), flip_flop.get_data());
+        Pin::connect(test_pin_clock.get_output(), flip_flop.get_clock());
+        Pin::connect(test_pin_set.get_output(), flip_flop.get_set());
+        Pin::connect(test_pin_reset.get_output(), flip_flop.get_reset());
+
+        let read = |flip_flop: &DFlipFlop| flip_flop.get_output().borrow().read();
+
+        for clock in [false, true] {
+            test_pin_clock.set_drive(DriveValue::Strong(clock));
+            settle(&mut flip_flop);
+
+            test_pin_set.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            assert_eq!(read(&flip_flop), LogicValue::Driven(true));
+            test_pin_set.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            assert_eq!(read(&flip_flop), LogicValue::Driven(true));
+
+            test_pin_reset.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            assert_eq!(read(&flip_flop), LogicValue::Driven(false));
+            test_pin_reset.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            assert_eq!(read(&flip_flop), LogicValue::Driven(false));
+        }
+
+        // The value forced by set survives the next falling edge, and is replaced by the data on
+        // the next rising edge.
+        test_pin_set.set_drive(DriveValue::Strong(true));
+        settle(&mut flip_flop);
+        test_pin_set.set_drive(DriveValue::Strong(false));
+        settle(&mut flip_flop);
+        test_pin_clock.set_drive(DriveValue::Strong(false));
+        settle(&mut flip_flop);
+        assert_eq!(read(&flip_flop), LogicValue::Driven(true));
+        test_pin_clock.set_drive(DriveValue::Strong(true));
+        settle(&mut flip_flop);
+        assert_eq!(read(&flip_flop), LogicValue::Driven(false));
+    }
+
+    // Checks the setup and hold times given in the documentation, and that neither of them can be
+    // any shorter.
+    #[test]
+    fn test_d_flip_flop_setup_hold() {
+        const SETUP: usize = 2;
+        const HOLD: usize = 1;
+
+        // Changes the data from !value to value, then raises the clock after `setup` ticks and
+        // changes the data back after another `hold` ticks, returning the captured value, or an
+        // error if the flip-flop doesn't settle.
+        let capture = |value: bool, setup: usize, hold: usize| {
+            let mut flip_flop = DFlipFlop::new();
+            let mut test_pin_data = TestPin::new(DriveValue::Strong(!value));
+            let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+            Pin::connect(test_pin_data.get_output(), flip_flop.get_data());
+            Pin::connect(test_pin_clock.get_output(), flip_flop.get_clock());
+            settle(&mut flip_flop);
+
+            test_pin_data.set_drive(DriveValue::Strong(value));
+            (0..setup).for_each(|_| {
+                tick(&mut flip_flop);
+            });
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            (0..hold).for_each(|_| {
+                tick(&mut flip_flop);
+            });
+            test_pin_data.set_drive(DriveValue::Strong(!value));
+            match settle_with_limit(&mut flip_flop, 100) {
+                Ok(_) => flip_flop.get_output().borrow().read(),
+                Err(_) => LogicValue::Error,
+            }
+        };
+
+        for value in [false, true] {
+            assert_eq!(capture(value, SETUP, HOLD), LogicValue::Driven(value));
+            assert_eq!(
+                capture(value, SETUP + 5, HOLD + 5),
+                LogicValue::Driven(value)
+            );
+        }
+        assert!([false, true]
+            .iter()
+            .any(|value| capture(*value, SETUP - 1, HOLD) != LogicValue::Driven(*value)));
+        assert!([false, true]
+            .iter()
+            .any(|value| capture(*value, SETUP, HOLD - 1) != LogicValue::Driven(*value)));
+    }
+}
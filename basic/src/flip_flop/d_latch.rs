@@ -0,0 +1,234 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{NorGate, NotGate, TransmissionGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A gated D latch. While `enable` is high the latch is transparent, and `output` follows `data`.
+/// While `enable` is low the latch holds whatever `data` was when `enable` fell.
+///
+/// The value is stored in a loop of two NOR gates. While enabled, one `TransmissionGate` connects
+/// `data` to the start of the loop, and while disabled another closes the loop instead. Because
+/// the loop is opened while the latch is enabled, any error stored in it is flushed out, and
+/// because `data` is disconnected while the latch is disabled, an error on `data` can't get in.
+///
+/// The other inputs of the NOR gates are the asynchronous `set` and `reset`, which force the output
+/// regardless of `enable`. They're weakly pulled low, so they can be left unconnected. While either
+/// is high `output_inverted` isn't necessarily the inverse of `output`. The start of the loop is
+/// also weakly pulled low, so the latch powers up storing `false`.
+#[derive(Device)]
+pub struct DLatch {
+    #[child]
+    not_gate: NotGate,
+
+    #[child]
+    input_gate: TransmissionGate,
+
+    #[child]
+    feedback_gate: TransmissionGate,
+
+    #[child]
+    nor_gate_1: NorGate,
+
+    #[child]
+    nor_gate_2: NorGate,
+
+    #[child]
+    storage_pull_down: Constant,
+
+    #[child]
+    set_pull_down: Constant,
+
+    #[child]
+    reset_pull_down: Constant,
+
+    #[pin]
+    data: Rc<RefCell<Pin>>,
+
+    #[pin]
+    enable: Rc<RefCell<Pin>>,
+
+    #[pin]
+    set: Rc<RefCell<Pin>>,
+
+    #[pin]
+    reset: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output_inverted: Rc<RefCell<Pin>>,
+}
+
+impl DLatch {
+    /// Creates a new D latch.
+    pub fn new() -> Self {
+        let not_gate = NotGate::new();
+        let input_gate = TransmissionGate::new();
+        let feedback_gate = TransmissionGate::new();
+        let nor_gate_1 = NorGate::new(2);
+        let nor_gate_2 = NorGate::new(2);
+        let storage_pull_down = Constant::new_weak(false);
+        let set_pull_down = Constant::new_weak(false);
+        let reset_pull_down = Constant::new_weak(false);
+        let data = input_gate.get_input().clone();
+        let enable = not_gate.get_input().clone();
+        let set = nor_gate_1.get_input()[1].clone();
+        let reset = nor_gate_2.get_input()[0].clone();
+        let output = nor_gate_2.get_output().clone();
+        let output_inverted = nor_gate_1.get_output().clone();
+
+        // The input gate is enabled by enable, and the feedback gate by its inverse.
+        Pin::connect(&enable, input_gate.get_enable());
+        Pin::connect(not_gate.get_output(), input_gate.get_enable_inverted());
+        Pin::connect(not_gate.get_output(), feedback_gate.get_enable());
+        Pin::connect(&enable, feedback_gate.get_enable_inverted());
+
+        // Both gates drive the start of the loop, which goes through both NOR gates and back. The
+        // loop enters the first NOR gate furthest from its output and the second nearest to it, so
+        // they take different numbers of ticks to rise, and a glitch can't circulate forever.
+        Pin::connect(input_gate.get_output(), &nor_gate_1.get_input()[0]);
+        Pin::connect(feedback_gate.get_output(), &nor_gate_1.get_input()[0]);
+        Pin::connect(nor_gate_1.get_output(), &nor_gate_2.get_input()[1]);
+        Pin::connect(nor_gate_2.get_output(), feedback_gate.get_input());
+
+        Pin::connect(storage_pull_down.get_output(), &nor_gate_1.get_input()[0]);
+        Pin::connect(set_pull_down.get_output(), &set);
+        Pin::connect(reset_pull_down.get_output(), &reset);
+
+        Self {
+            not_gate,
+            input_gate,
+            feedback_gate,
+            nor_gate_1,
+            nor_gate_2,
+            storage_pull_down,
+            set_pull_down,
+            reset_pull_down,
+            data,
+            enable,
+            set,
+            reset,
+            output,
+            output_inverted,
+        }
+    }
+}
+
+impl Default for DLatch {
+    fn default() -> Self {
+        DLatch::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, DriveValue, LogicValue, TestPin};
+
+    #[test]
+    fn test_d_latch() {
+        let mut latch = DLatch::default();
+        let mut test_pin_data = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_enable = TestPin::new(DriveValue::Strong(false));
+
+        let check = |latch: &DLatch, value: bool| {
+            assert_eq!(
+                latch.get_output().borrow().read(),
+                LogicValue::Driven(value)
+            );
+            assert_eq!(
+                latch.get_output_inverted().borrow().read(),
+                LogicValue::Driven(!value)
+            );
+        };
+
+        Pin::connect(test_pin_data.get_output(), latch.get_data());
+        Pin::connect(test_pin_enable.get_output(), latch.get_enable());
+
+        settle(&mut latch);
+        check(&latch, false);
+
+        (0..3).for_each(|_| {
+            // While disabled, changes to the data are ignored.
+            test_pin_data.set_drive(DriveValue::Strong(true));
+            settle(&mut latch);
+            check(&latch, false);
+
+            // While enabled, the output follows the data.
+            test_pin_enable.set_drive(DriveValue::Strong(true));
+            settle(&mut latch);
+            check(&latch, true);
+
+            test_pin_data.set_drive(DriveValue::Strong(false));
+            settle(&mut latch);
+            check(&latch, false);
+
+            test_pin_data.set_drive(DriveValue::Strong(true));
+            settle(&mut latch);
+            check(&latch, true);
+
+            // Once disabled again, the last value is held.
+            test_pin_enable.set_drive(DriveValue::Strong(false));
+            settle(&mut latch);
+            check(&latch, true);
+
+            test_pin_data.set_drive(DriveValue::Strong(false));
+            settle(&mut latch);
+            check(&latch, true);
+
+            test_pin_enable.set_drive(DriveValue::Strong(true));
+            settle(&mut latch);
+            check(&latch, false);
+
+            test_pin_enable.set_drive(DriveValue::Strong(false));
+            settle(&mut latch);
+            check(&latch, false);
+        });
+    }
+
+    #[test]
+    fn test_d_latch_set_reset() {
+        let mut latch = DLatch::default();
+        let mut test_pin_data = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_enable = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_set = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_reset = TestPin::new(DriveValue::Strong(false));
+
+        Pin::connect(test_pin_data.get_output(), latch.get_data());
+        Pin::connect(test_pin_enable.get_output(), latch.get_enable());
+        Pin::connect(test_pin_set.get_output(), latch.get_set());
+        Pin::connect(test_pin_reset.get_output(), latch.get_reset());
+
+        let read = |latch: &DLatch| latch.get_output().borrow().read();
+
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(false));
+
+        // Set and reset work while the latch is disabled.
+        test_pin_set.set_drive(DriveValue::Strong(true));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(true));
+        test_pin_set.set_drive(DriveValue::Strong(false));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(true));
+
+        test_pin_reset.set_drive(DriveValue::Strong(true));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(false));
+        test_pin_reset.set_drive(DriveValue::Strong(false));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(false));
+
+        // They override the data while the latch is enabled, until they're released.
+        test_pin_data.set_drive(DriveValue::Strong(true));
+        test_pin_enable.set_drive(DriveValue::Strong(true));
+        test_pin_reset.set_drive(DriveValue::Strong(true));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(false));
+        test_pin_reset.set_drive(DriveValue::Strong(false));
+        settle(&mut latch);
+        assert_eq!(read(&latch), LogicValue::Driven(true));
+    }
+}
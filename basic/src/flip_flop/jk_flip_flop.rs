@@ -0,0 +1,156 @@
+use crate::DFlipFlop;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A rising edge triggered JK flip-flop. On the rising edge of `clock` it holds its value if
+/// neither `j` nor `k` is high, sets if only `j` is high, resets if only `k` is high, and toggles
+/// if both are high.
+///
+/// It's a `DFlipFlop` whose data input is `(j AND NOT output) OR (NOT k AND output)`, so it has the
+/// same setup and hold times, plus the few ticks it takes the gates in front of it to settle, and
+/// the same weakly pulled down asynchronous `set` and `reset` inputs.
+#[derive(Device)]
+pub struct JkFlipFlop {
+    #[child]
+    not_gate: NotGate,
+
+    #[child]
+    and_gate_j: AndGate,
+
+    #[child]
+    and_gate_k: AndGate,
+
+    #[child]
+    or_gate: OrGate,
+
+    #[child]
+    d_flip_flop: DFlipFlop,
+
+    #[pin]
+    j: Rc<RefCell<Pin>>,
+
+    #[pin]
+    k: Rc<RefCell<Pin>>,
+
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+
+    #[pin]
+    set: Rc<RefCell<Pin>>,
+
+    #[pin]
+    reset: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output_inverted: Rc<RefCell<Pin>>,
+}
+
+impl JkFlipFlop {
+    /// Creates a new JK flip-flop.
+    pub fn new() -> Self {
+        let not_gate = NotGate::new();
+        let and_gate_j = AndGate::new(2);
+        let and_gate_k = AndGate::new(2);
+        let or_gate = OrGate::new(2);
+        let d_flip_flop = DFlipFlop::new();
+        let j = and_gate_j.get_input()[0].clone();
+        let k = not_gate.get_input().clone();
+        let clock = d_flip_flop.get_clock().clone();
+        let set = d_flip_flop.get_set().clone();
+        let reset = d_flip_flop.get_reset().clone();
+        let output = d_flip_flop.get_output().clone();
+        let output_inverted = d_flip_flop.get_output_inverted().clone();
+
+        Pin::connect(&output_inverted, &and_gate_j.get_input()[1]);
+        Pin::connect(not_gate.get_output(), &and_gate_k.get_input()[0]);
+        Pin::connect(&output, &and_gate_k.get_input()[1]);
+        Pin::connect(and_gate_j.get_output(), &or_gate.get_input()[0]);
+        Pin::connect(and_gate_k.get_output(), &or_gate.get_input()[1]);
+        Pin::connect(or_gate.get_output(), d_flip_flop.get_data());
+
+        Self {
+            not_gate,
+            and_gate_j,
+            and_gate_k,
+            or_gate,
+            d_flip_flop,
+            j,
+            k,
+            clock,
+            set,
+            reset,
+            output,
+            output_inverted,
+        }
+    }
+}
+
+impl Default for JkFlipFlop {
+    fn default() -> Self {
+        JkFlipFlop::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, DriveValue, LogicValue, TestPin};
+
+    #[test]
+    fn test_jk_flip_flop() {
+        let mut flip_flop = JkFlipFlop::default();
+        let mut test_pin_j = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_k = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+
+        Pin::connect(test_pin_j.get_output(), flip_flop.get_j());
+        Pin::connect(test_pin_k.get_output(), flip_flop.get_k());
+        Pin::connect(test_pin_clock.get_output(), flip_flop.get_clock());
+
+        let check = |flip_flop: &JkFlipFlop, value: bool| {
+            assert_eq!(
+                flip_flop.get_output().borrow().read(),
+                LogicValue::Driven(value)
+            );
+            assert_eq!(
+                flip_flop.get_output_inverted().borrow().read(),
+                LogicValue::Driven(!value)
+            );
+        };
+
+        settle(&mut flip_flop);
+        check(&flip_flop, false);
+
+        // Each step is (j, k, expected output after the next rising edge).
+        let steps = [
+            (false, false, false),
+            (true, false, true),
+            (false, false, true),
+            (true, false, true),
+            (false, true, false),
+            (false, false, false),
+            (false, true, false),
+            (true, true, true),
+            (true, true, false),
+            (true, true, true),
+            (false, true, false),
+        ];
+        for (j, k, expected) in steps {
+            test_pin_j.set_drive(DriveValue::Strong(j));
+            test_pin_k.set_drive(DriveValue::Strong(k));
+            settle(&mut flip_flop);
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            check(&flip_flop, expected);
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            check(&flip_flop, expected);
+        }
+    }
+}
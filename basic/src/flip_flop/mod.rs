@@ -1,3 +1,11 @@
+mod d_flip_flop;
+mod d_latch;
+mod jk_flip_flop;
 mod sr_latch;
+mod t_flip_flop;
 
+pub use d_flip_flop::DFlipFlop;
+pub use d_latch::DLatch;
+pub use jk_flip_flop::JkFlipFlop;
 pub use sr_latch::SrLatch;
+pub use t_flip_flop::TFlipFlop;
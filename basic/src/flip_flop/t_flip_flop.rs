@@ -0,0 +1,124 @@
+use crate::DFlipFlop;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::XorGate;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A rising edge triggered T flip-flop. On the rising edge of `clock` it toggles its value if
+/// `toggle` is high, and holds it otherwise.
+///
+/// It's a `DFlipFlop` whose data input is `toggle XOR output`, so it has the same setup and hold
+/// times, plus the few ticks it takes the XOR gate to settle, and the same weakly pulled down
+/// asynchronous `set` and `reset` inputs.
+#[derive(Device)]
+pub struct TFlipFlop {
+    #[child]
+    xor_gate: XorGate,
+
+    #[child]
+    d_flip_flop: DFlipFlop,
+
+    #[pin]
+    toggle: Rc<RefCell<Pin>>,
+
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+
+    #[pin]
+    set: Rc<RefCell<Pin>>,
+
+    #[pin]
+    reset: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output_inverted: Rc<RefCell<Pin>>,
+}
+
+impl TFlipFlop {
+    /// Creates a new T flip-flop.
+    pub fn new() -> Self {
+        let xor_gate = XorGate::new();
+        let d_flip_flop = DFlipFlop::new();
+        let toggle = xor_gate.get_a_input().clone();
+        let clock = d_flip_flop.get_clock().clone();
+        let set = d_flip_flop.get_set().clone();
+        let reset = d_flip_flop.get_reset().clone();
+        let output = d_flip_flop.get_output().clone();
+        let output_inverted = d_flip_flop.get_output_inverted().clone();
+
+        Pin::connect(&output, xor_gate.get_b_input());
+        Pin::connect(xor_gate.get_output(), d_flip_flop.get_data());
+
+        Self {
+            xor_gate,
+            d_flip_flop,
+            toggle,
+            clock,
+            set,
+            reset,
+            output,
+            output_inverted,
+        }
+    }
+}
+
+impl Default for TFlipFlop {
+    fn default() -> Self {
+        TFlipFlop::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, DriveValue, LogicValue, TestPin};
+
+    #[test]
+    fn test_t_flip_flop() {
+        let mut flip_flop = TFlipFlop::default();
+        let mut test_pin_toggle = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+
+        Pin::connect(test_pin_toggle.get_output(), flip_flop.get_toggle());
+        Pin::connect(test_pin_clock.get_output(), flip_flop.get_clock());
+
+        let check = |flip_flop: &TFlipFlop, value: bool| {
+            assert_eq!(
+                flip_flop.get_output().borrow().read(),
+                LogicValue::Driven(value)
+            );
+            assert_eq!(
+                flip_flop.get_output_inverted().borrow().read(),
+                LogicValue::Driven(!value)
+            );
+        };
+
+        settle(&mut flip_flop);
+        check(&flip_flop, false);
+
+        // Each step is (toggle, expected output after the next rising edge).
+        let steps = [
+            (false, false),
+            (true, true),
+            (true, false),
+            (false, false),
+            (true, true),
+            (false, true),
+            (true, false),
+        ];
+        for (toggle, expected) in steps {
+            test_pin_toggle.set_drive(DriveValue::Strong(toggle));
+            settle(&mut flip_flop);
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut flip_flop);
+            check(&flip_flop, expected);
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut flip_flop);
+            check(&flip_flop, expected);
+        }
+    }
+}
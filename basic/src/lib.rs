@@ -2,7 +2,19 @@
 #![deny(missing_docs)]
 
 mod adder;
+mod arithmetic;
 mod flip_flop;
+mod memory;
+mod routing;
+mod sequential;
 
-pub use adder::{FullAdder, HalfAdder, RippleCarryAdder};
-pub use flip_flop::SrLatch;
+pub use adder::{
+    AdderSubtractor, CarryLookaheadAdder, CarrySelectAdder, FullAdder, HalfAdder, RippleCarryAdder,
+};
+pub use arithmetic::{
+    Alu, AluOperation, ArrayMultiplier, BarrelShifter, Comparator, RestoringDivider,
+};
+pub use flip_flop::{DFlipFlop, DLatch, JkFlipFlop, SrLatch, TFlipFlop};
+pub use memory::{BehavioralSram, IntelHexError, Rom, Sram};
+pub use routing::{Decoder, Demultiplexer, Multiplexer, PriorityEncoder};
+pub use sequential::{Counter, Register, RegisterFile, ShiftRegister, ShiftRegisterMode};
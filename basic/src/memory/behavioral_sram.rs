@@ -0,0 +1,320 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Behavioral, Device, DeviceContainer, DriveValue, LogicValue, Pin};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+/// A model of an `Sram` which is simulated in Rust rather than with transistors, so memories with
+/// far more words can be simulated. It has exactly the same `Pin`s and behaves the same way, so it
+/// can be swapped for an `Sram` without changing anything else.
+///
+/// The contents are a `LogicValue` per bit, which start out as `Error` since the contents of an
+/// `Sram` are undefined until written. While `chip_select` is high, an `address` or control `Pin`
+/// that isn't driven makes the `BehavioralSram` drive errors onto `data`, and nothing is written.
+#[derive(Device)]
+#[behavioral]
+pub struct BehavioralSram {
+    #[pins]
+    address: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    data: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    chip_select: Rc<RefCell<Pin>>,
+    #[pin]
+    write_enable: Rc<RefCell<Pin>>,
+    #[pin]
+    output_enable: Rc<RefCell<Pin>>,
+    contents: RefCell<Vec<LogicValue>>,
+}
+
+impl BehavioralSram {
+    /// Creates a new `BehavioralSram` with the desired number of address bits, and words of the
+    /// desired width. Bit `b` of word `w` is at index `w * width + b` of the contents.
+    pub fn new(address_bits: usize, width: usize) -> Self {
+        if address_bits == 0 {
+            panic!("BehavioralSram address bits must be non-zero.")
+        }
+        if width == 0 {
+            panic!("BehavioralSram width must be non-zero.")
+        }
+
+        let new_pins = |count: usize| -> Vec<Rc<RefCell<Pin>>> {
+            (0..count)
+                .map(|_| Pin::new(DriveValue::HighImpedance))
+                .collect()
+        };
+
+        Self {
+            address: new_pins(address_bits),
+            data: new_pins(width),
+            chip_select: Pin::new(DriveValue::HighImpedance),
+            write_enable: Pin::new(DriveValue::HighImpedance),
+            output_enable: Pin::new(DriveValue::HighImpedance),
+            contents: RefCell::new(vec![LogicValue::Error; (1 << address_bits) * width]),
+        }
+    }
+}
+
+impl Behavioral for BehavioralSram {
+    fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+        let mut inputs = self.address.clone();
+        inputs.extend(self.data.iter().cloned());
+        inputs.push(self.chip_select.clone());
+        inputs.push(self.write_enable.clone());
+        inputs.push(self.output_enable.clone());
+        inputs
+    }
+
+    fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+        self.data.clone()
+    }
+
+    fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue> {
+        let width = self.data.len();
+        let (address, rest) = inputs.split_at(self.address.len());
+        let (data, control) = rest.split_at(width);
+        let high_impedance = vec![DriveValue::HighImpedance; width];
+        let errors = vec![DriveValue::Error; width];
+
+        match control[0] {
+            LogicValue::Driven(true) => (),
+            LogicValue::Driven(false) => return high_impedance,
+            _ => return errors,
+        }
+
+        let word = address.iter().rev().try_fold(0, |word, bit| match bit {
+            LogicValue::Driven(bit) => Some(word << 1 | *bit as usize),
+            _ => None,
+        });
+        match (word, control[1], control[2]) {
+            (Some(word), LogicValue::Driven(true), _) => {
+                // Like a latch, anything but a driven value is stored as an error.
+                let mut contents = self.contents.borrow_mut();
+                for (bit, value) in data.iter().enumerate() {
+                    contents[word * width + bit] = match value {
+                        LogicValue::Driven(_) => *value,
+                        _ => LogicValue::Error,
+                    };
+                }
+                high_impedance
+            }
+            (Some(word), LogicValue::Driven(false), LogicValue::Driven(true)) => {
+                let contents = self.contents.borrow();
+                contents[word * width..(word + 1) * width]
+                    .iter()
+                    .map(|value| (*value).into())
+                    .collect()
+            }
+            (_, LogicValue::Driven(false), LogicValue::Driven(false)) => high_impedance,
+            _ => errors,
+        }
+    }
+
+    fn hash_state(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.contents.borrow().hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sram;
+    use foundation::{Simulator, TestBus, TestPin};
+
+    fn read_values(pins: &[Rc<RefCell<Pin>>]) -> Vec<LogicValue> {
+        pins.iter().map(|pin| pin.borrow().read()).collect()
+    }
+
+    // Writes a few words, then reads every word back, including ones that were never written, and
+    // checks the bus is released when it should be. Returns the values of `data` after every step,
+    // so different memories can be compared. `control` is `chip_select`, `write_enable`, and
+    // `output_enable`.
+    fn exercise(
+        device: &dyn AnyDevice,
+        address: &[Rc<RefCell<Pin>>],
+        data: &[Rc<RefCell<Pin>>],
+        control: [&Rc<RefCell<Pin>>; 3],
+        writes: &[(usize, usize)],
+    ) -> Vec<Vec<LogicValue>> {
+        let mut test_bus_address = TestBus::new(address, DriveValue::HighImpedance);
+        let mut test_bus_data = TestBus::new(data, DriveValue::HighImpedance);
+        let mut test_pin_chip_select = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_write_enable = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_output_enable = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin_chip_select.get_output(), control[0]);
+        Pin::connect(test_pin_write_enable.get_output(), control[1]);
+        Pin::connect(test_pin_output_enable.get_output(), control[2]);
+        let mut simulator = Simulator::new(device);
+        simulator.settle();
+
+        let mut history = vec![];
+        test_pin_chip_select.set_drive(DriveValue::Strong(true));
+        for (word, value) in writes.iter() {
+            test_bus_address.set_value(*word);
+            test_bus_data.set_value(*value);
+            simulator.settle();
+            test_pin_write_enable.set_drive(DriveValue::Strong(true));
+            simulator.settle();
+            history.push(read_values(data));
+            test_pin_write_enable.set_drive(DriveValue::Strong(false));
+            simulator.settle();
+        }
+
+        test_bus_data.set_drive(DriveValue::HighImpedance);
+        test_pin_output_enable.set_drive(DriveValue::Strong(true));
+        for word in 0..1 << address.len() {
+            test_bus_address.set_value(word);
+            simulator.settle();
+            history.push(read_values(data));
+        }
+
+        for (chip_select, write_enable, output_enable) in [
+            (false, false, true),
+            (true, false, false),
+            (true, true, true),
+        ] {
+            test_pin_chip_select.set_drive(DriveValue::Strong(chip_select));
+            test_pin_write_enable.set_drive(DriveValue::Strong(write_enable));
+            test_pin_output_enable.set_drive(DriveValue::Strong(output_enable));
+            simulator.settle();
+            history.push(read_values(data));
+        }
+        history
+    }
+
+    // The same stimulus gives the same values on `data` as the transistor-level `Sram`.
+    #[test]
+    fn test_behavioral_sram_matches_sram() {
+        for (address_bits, width) in [(1, 1), (2, 3), (3, 2)] {
+            let words = 1 << address_bits;
+            let writes: Vec<(usize, usize)> = (0..words - 1)
+                .map(|word| (word, (word * 5 + 3) % (1 << width)))
+                .collect();
+
+            let sram = Sram::new(address_bits, width);
+            let expected = exercise(
+                &sram,
+                sram.get_address(),
+                sram.get_data(),
+                [
+                    sram.get_chip_select(),
+                    sram.get_write_enable(),
+                    sram.get_output_enable(),
+                ],
+                &writes,
+            );
+            let behavioral_sram = BehavioralSram::new(address_bits, width);
+            let actual = exercise(
+                &behavioral_sram,
+                behavioral_sram.get_address(),
+                behavioral_sram.get_data(),
+                [
+                    behavioral_sram.get_chip_select(),
+                    behavioral_sram.get_write_enable(),
+                    behavioral_sram.get_output_enable(),
+                ],
+                &writes,
+            );
+            assert_eq!(actual, expected);
+
+            // The last word was never written.
+            let last = &actual[writes.len() + words - 1];
+            assert!(last.iter().all(|value| *value == LogicValue::Error));
+        }
+    }
+
+    // Far larger than would be practical with transistors.
+    #[test]
+    fn test_large_behavioral_sram() {
+        let address_bits = 12;
+        let width = 16;
+        let writes: Vec<(usize, usize)> = (0..1 << address_bits)
+            .map(|word| (word, (word * 7919) % (1 << width)))
+            .collect();
+        let sram = BehavioralSram::new(address_bits, width);
+        let history = exercise(
+            &sram,
+            sram.get_address(),
+            sram.get_data(),
+            [
+                sram.get_chip_select(),
+                sram.get_write_enable(),
+                sram.get_output_enable(),
+            ],
+            &writes,
+        );
+        for (word, value) in writes.iter() {
+            let expected: Vec<LogicValue> = (0..width)
+                .map(|bit| LogicValue::Driven((value >> bit) & 1 == 1))
+                .collect();
+            assert_eq!(history[writes.len() + word], expected);
+        }
+    }
+
+    // An undriven address doesn't write anything, and drives errors onto the bus.
+    #[test]
+    fn test_behavioral_sram_undriven_address() {
+        let sram = BehavioralSram::new(1, 2);
+        let mut test_bus_data = TestBus::new(sram.get_data(), DriveValue::HighImpedance);
+        let mut test_pin_chip_select = TestPin::new(DriveValue::Strong(true));
+        let mut test_pin_write_enable = TestPin::new(DriveValue::Strong(true));
+        let test_pin_output_enable = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin_chip_select.get_output(), sram.get_chip_select());
+        Pin::connect(test_pin_write_enable.get_output(), sram.get_write_enable());
+        Pin::connect(
+            test_pin_output_enable.get_output(),
+            sram.get_output_enable(),
+        );
+        test_bus_data.set_value(3);
+        let mut simulator = Simulator::new(&sram);
+        simulator.settle();
+        assert_eq!(read_values(sram.get_data()), vec![LogicValue::Error; 2]);
+        assert!(sram
+            .contents
+            .borrow()
+            .iter()
+            .all(|value| *value == LogicValue::Error));
+
+        // Deselecting the chip releases the bus again.
+        test_pin_write_enable.set_drive(DriveValue::Strong(false));
+        test_pin_chip_select.set_drive(DriveValue::Strong(false));
+        test_bus_data.set_drive(DriveValue::HighImpedance);
+        simulator.settle();
+        assert_eq!(
+            read_values(sram.get_data()),
+            vec![LogicValue::HighImpedance; 2]
+        );
+    }
+
+    // The contents are part of the state, so a write is never mistaken for an oscillation.
+    #[test]
+    fn test_behavioral_sram_hash_state() {
+        let sram = BehavioralSram::new(1, 1);
+        let before = sram.hash_state();
+        assert!(before.is_some());
+        // Write a one to word zero, i.e. address, data, chip select, write enable, output enable.
+        sram.evaluate(&[
+            LogicValue::Driven(false),
+            LogicValue::Driven(true),
+            LogicValue::Driven(true),
+            LogicValue::Driven(true),
+            LogicValue::Driven(false),
+        ]);
+        assert_ne!(sram.hash_state(), before);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_behavioral_sram_address_bits() {
+        BehavioralSram::new(0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_behavioral_sram_width() {
+        BehavioralSram::new(1, 0);
+    }
+}
@@ -0,0 +1,7 @@
+mod behavioral_sram;
+mod rom;
+mod sram;
+
+pub use behavioral_sram::BehavioralSram;
+pub use rom::{IntelHexError, Rom};
+pub use sram::Sram;
@@ -0,0 +1,358 @@
+use crate::Multiplexer;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// The largest span of addresses the data records of an Intel HEX file can cover, since every byte
+/// becomes part of a `Multiplexer` built from `Transistor`s.
+const MAX_INTEL_HEX_BYTES: usize = 1 << 16;
+
+/// A read-only memory. `data` is always the word selected by `address`.
+///
+/// The contents are baked in when the `Rom` is created, by connecting the inputs of a `Multiplexer`
+/// to high or low `Constant`s. Addresses have enough bits to select every word, and at least one.
+/// If the number of words isn't a power of two, the addresses past the end read as zero.
+#[derive(Device)]
+pub struct Rom {
+    #[child]
+    strong_false: Constant,
+    #[child]
+    strong_true: Constant,
+    #[child]
+    multiplexer: Multiplexer,
+    #[pins]
+    address: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    data: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl Rom {
+    /// Creates a new `Rom` with words of the desired width, holding the given contents. Every word
+    /// must fit in the width.
+    pub fn new(contents: &[u64], width: usize) -> Self {
+        if contents.is_empty() {
+            panic!("Rom contents must be non-empty.")
+        }
+        if width == 0 || width > 64 {
+            panic!("Rom width must be between 1 and 64.")
+        }
+        if width < 64 && contents.iter().any(|word| word >> width != 0) {
+            panic!("Rom contents must fit in the width.")
+        }
+
+        let address_bits = (usize::BITS - (contents.len() - 1).leading_zeros()).max(1) as usize;
+        let strong_false = Constant::new_strong(false);
+        let strong_true = Constant::new_strong(true);
+        let multiplexer = Multiplexer::new(address_bits, width);
+        let address = multiplexer.get_select().to_vec();
+        let data = multiplexer.get_output().to_vec();
+
+        for (index, input) in multiplexer.get_input().iter().enumerate() {
+            let (word, bit) = (index / width, index % width);
+            let constant = match contents.get(word) {
+                Some(value) if (value >> bit) & 1 == 1 => &strong_true,
+                _ => &strong_false,
+            };
+            Pin::connect(constant.get_output(), input);
+        }
+
+        Self {
+            strong_false,
+            strong_true,
+            multiplexer,
+            address,
+            data,
+        }
+    }
+
+    /// Creates a new `Rom` with words of the desired width, holding the contents of a flat binary
+    /// image. Each word is stored in as many bytes as it takes to hold the width, in little-endian
+    /// order, and any bits of those bytes beyond the width are ignored. If the image ends part way
+    /// through a word, the rest of the word is zero.
+    pub fn from_binary(image: &[u8], width: usize) -> Self {
+        if width == 0 || width > 64 {
+            panic!("Rom width must be between 1 and 64.")
+        }
+
+        let mask = u64::MAX >> (64 - width);
+        let contents: Vec<u64> = image
+            .chunks(width.div_ceil(8))
+            .map(|bytes| {
+                let word = bytes
+                    .iter()
+                    .rev()
+                    .fold(0u64, |word, byte| word << 8 | *byte as u64);
+                word & mask
+            })
+            .collect();
+        Self::new(&contents, width)
+    }
+
+    /// Creates a new `Rom` with words of the desired width, holding the contents of an Intel HEX
+    /// file. The data records are assembled into a flat binary image, with any gaps filled with
+    /// zeros, which is then used as by [`Rom::from_binary`]. The image starts at the lowest address
+    /// of any data record, so that address is address zero of the `Rom`. Extended segment and
+    /// extended linear address records are supported, and start address records are ignored.
+    ///
+    /// An error is returned if the data records span more than 64 KiB, or if the width isn't
+    /// between 1 and 64.
+    pub fn from_intel_hex(text: &str, width: usize) -> Result<Self, IntelHexError> {
+        if width == 0 || width > 64 {
+            return Err(IntelHexError {
+                line: 0,
+                message: "width must be between 1 and 64".to_string(),
+            });
+        }
+
+        let mut records: Vec<(usize, Vec<u8>)> = vec![];
+        let mut lowest = usize::MAX;
+        let mut highest = 0;
+        let mut base = 0;
+        let mut ended = false;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| IntelHexError {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            if ended {
+                return Err(error("record after the end of file record"));
+            }
+
+            let hex = line
+                .strip_prefix(':')
+                .ok_or_else(|| error("record doesn't start with ':'"))?;
+            if hex.len() % 2 != 0 || !hex.is_ascii() {
+                return Err(error("record isn't a whole number of hexadecimal bytes"));
+            }
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|start| u8::from_str_radix(&hex[start..start + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| error("record contains an invalid hexadecimal digit"))?;
+            if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+                return Err(error("record length doesn't match its byte count"));
+            }
+            if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+                return Err(error("record checksum doesn't match"));
+            }
+
+            let offset = (bytes[1] as usize) << 8 | bytes[2] as usize;
+            let payload = &bytes[4..bytes.len() - 1];
+            let payload_value = || {
+                payload
+                    .iter()
+                    .fold(0usize, |value, byte| value << 8 | *byte as usize)
+            };
+            match bytes[3] {
+                0x00 => {
+                    let start = base + offset;
+                    lowest = lowest.min(start);
+                    highest = highest.max(start + payload.len());
+                    if highest - lowest > MAX_INTEL_HEX_BYTES {
+                        return Err(error("data records span more than 64 KiB"));
+                    }
+                    records.push((start, payload.to_vec()));
+                }
+                0x01 => ended = true,
+                0x02 if payload.len() == 2 => base = payload_value() << 4,
+                0x04 if payload.len() == 2 => base = payload_value() << 16,
+                0x03 | 0x05 => (),
+                0x02 | 0x04 => return Err(error("address record must have two data bytes")),
+                _ => return Err(error("unknown record type")),
+            }
+        }
+
+        if !ended {
+            return Err(IntelHexError {
+                line: text.lines().count(),
+                message: "missing end of file record".to_string(),
+            });
+        }
+        if highest <= lowest {
+            return Err(IntelHexError {
+                line: text.lines().count(),
+                message: "no data records".to_string(),
+            });
+        }
+
+        let mut image = vec![0; highest - lowest];
+        for (start, payload) in records.iter() {
+            image[start - lowest..start - lowest + payload.len()].copy_from_slice(payload);
+        }
+        Ok(Self::from_binary(&image, width))
+    }
+}
+
+/// The reason an Intel HEX file couldn't be loaded by [`Rom::from_intel_hex`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntelHexError {
+    /// The line number, starting from one, where the problem was found.
+    line: usize,
+
+    /// A description of the problem.
+    message: String,
+}
+
+impl IntelHexError {
+    /// Gets the line number, starting from one, where the problem was found. Problems with the
+    /// file as a whole are reported on the last line, and a bad width on line zero.
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    /// Gets a description of the problem.
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for IntelHexError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for IntelHexError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, Simulator, TestBus};
+
+    // Reads every address of the ROM, including any past the end of its contents.
+    fn read_all(rom: &Rom) -> Vec<u64> {
+        let mut test_bus_address = TestBus::new(rom.get_address(), DriveValue::Strong(false));
+        let mut simulator = Simulator::new(rom);
+
+        (0..1 << rom.get_address().len())
+            .map(|address: usize| {
+                test_bus_address.set_value(address);
+                simulator.settle();
+                read_bus(rom.get_data()).unwrap() as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rom() {
+        assert_eq!(read_all(&Rom::new(&[1], 1)), [1, 0]);
+        assert_eq!(read_all(&Rom::new(&[3, 0, 2], 2)), [3, 0, 2, 0]);
+        let contents = [0x12, 0xff, 0x00, 0x80, 0x7f, 0x01, 0xa5, 0x5a];
+        assert_eq!(read_all(&Rom::new(&contents, 8)), contents);
+    }
+
+    #[test]
+    fn test_rom_from_binary() {
+        let rom = Rom::from_binary(&[0x34, 0x12, 0xff, 0xff, 0x01], 12);
+        assert_eq!(rom.get_data().len(), 12);
+        assert_eq!(read_all(&rom), [0x234, 0xfff, 0x001, 0]);
+    }
+
+    #[test]
+    fn test_rom_from_intel_hex() {
+        // Two data records with a gap between them, an extended linear address record that adds
+        // nothing, and a start address record.
+        let text = "\
+:03000000010203F7
+:020006000405EF
+:020000040000FA
+:0400000500000000F7
+:00000001FF
+";
+        let rom = Rom::from_intel_hex(text, 8).unwrap();
+        assert_eq!(read_all(&rom), [1, 2, 3, 0, 0, 0, 4, 5]);
+    }
+
+    // The image starts at the lowest address, rather than zero.
+    #[test]
+    fn test_rom_from_intel_hex_segment_address() {
+        let text = ":020000020001FB\n:0100000007F8\n:0100020009F4\n:00000001FF\n";
+        let rom = Rom::from_intel_hex(text, 8).unwrap();
+        assert_eq!(read_all(&rom), [7, 0, 9, 0]);
+    }
+
+    // Data at 0x80000000 doesn't need 2 GiB of zeros before it.
+    #[test]
+    fn test_rom_from_intel_hex_linear_address() {
+        let text = ":0200000480007A\n:0100000007F8\n:00000001FF\n";
+        let rom = Rom::from_intel_hex(text, 8).unwrap();
+        assert_eq!(read_all(&rom), [7, 0]);
+    }
+
+    #[test]
+    fn test_rom_from_bad_intel_hex() {
+        for (text, line, message) in [
+            ("0100000007F8\n", 1, "record doesn't start with ':'"),
+            (
+                ":0100000007F\n",
+                1,
+                "record isn't a whole number of hexadecimal bytes",
+            ),
+            (
+                ":01000000G7F8\n",
+                1,
+                "record contains an invalid hexadecimal digit",
+            ),
+            (
+                ":0200000007F7\n",
+                1,
+                "record length doesn't match its byte count",
+            ),
+            (
+                ":0100000007F7\n:00000001FF\n",
+                1,
+                "record checksum doesn't match",
+            ),
+            (":00000006FA\n", 1, "unknown record type"),
+            (
+                ":0100000007F8\n:00000001FF\n:00000001FF\n",
+                3,
+                "record after the end of file record",
+            ),
+            (":0100000007F8\n", 1, "missing end of file record"),
+            (":00000001FF\n", 1, "no data records"),
+            (
+                ":0100000007F8\n:020000040001F9\n:0100000007F8\n:00000001FF\n",
+                3,
+                "data records span more than 64 KiB",
+            ),
+        ] {
+            let error = Rom::from_intel_hex(text, 8).err().unwrap();
+            assert_eq!((error.get_line(), error.get_message()), (line, message));
+        }
+
+        for width in [0, 65] {
+            let error = Rom::from_intel_hex(":0100000007F8\n:00000001FF\n", width)
+                .err()
+                .unwrap();
+            assert_eq!(
+                (error.get_line(), error.get_message()),
+                (0, "width must be between 1 and 64")
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_rom_contents() {
+        Rom::new(&[4], 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_rom_empty() {
+        Rom::new(&[], 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_rom_width() {
+        Rom::new(&[0], 65);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{DLatch, Decoder};
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, NandGate, NotGate, TransmissionGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A static RAM with 2^n words, made from one `DLatch` per bit. `data` is a bidirectional bus,
+/// which is read from while writing and driven while reading. Like a real SRAM, the contents are
+/// undefined at power-up, so a word may read as `Error` until it's first written.
+///
+/// While `chip_select` and `write_enable` are high, the latches of the word selected by `address`
+/// are enabled, so the word takes on the value of `data`, and it keeps that value after
+/// `write_enable` falls. `address` must be stable before `write_enable` rises, and stay stable
+/// until after it falls, or other words may be written too.
+///
+/// While `chip_select` and `output_enable` are high and `write_enable` is low, the selected word is
+/// driven onto `data`. Otherwise `data` is high impedance, so other devices can share the bus.
+///
+/// A `Decoder`, enabled by `chip_select`, turns `address` into one line per word. Each word has an
+/// AND gate which enables its latches for writing, and a NAND gate and a NOT gate which enable the
+/// `TransmissionGate`s between its latches and `data` for reading. Unlike a `TriStateBufferGate`,
+/// a disabled `TransmissionGate` doesn't pass on an error, so an undefined word can't interfere
+/// with reading or writing the others.
+#[derive(Device)]
+pub struct Sram {
+    #[child]
+    decoder: Decoder,
+    #[child]
+    not_write_enable_gate: NotGate,
+    #[child]
+    read_enable_gate: AndGate,
+    #[children]
+    write_gates: Vec<AndGate>,
+    #[children]
+    read_gates: Vec<NandGate>,
+    #[children]
+    read_not_gates: Vec<NotGate>,
+    #[children]
+    latches: Vec<DLatch>,
+    #[children]
+    output_gates: Vec<TransmissionGate>,
+    #[pins]
+    address: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    data: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    chip_select: Rc<RefCell<Pin>>,
+    #[pin]
+    write_enable: Rc<RefCell<Pin>>,
+    #[pin]
+    output_enable: Rc<RefCell<Pin>>,
+}
+
+impl Sram {
+    /// Creates a new `Sram` with the desired number of address bits, and words of the desired
+    /// width. The latch and transmission gate for word `w` and bit `b` are at index
+    /// `w * width + b`.
+    pub fn new(address_bits: usize, width: usize) -> Self {
+        if address_bits == 0 {
+            panic!("Sram address bits must be non-zero.")
+        }
+        if width == 0 {
+            panic!("Sram width must be non-zero.")
+        }
+
+        let words = 1 << address_bits;
+        let decoder = Decoder::new(address_bits);
+        let not_write_enable_gate = NotGate::new();
+        let read_enable_gate = AndGate::new(2);
+        let write_gates: Vec<AndGate> = (0..words).map(|_| AndGate::new(2)).collect();
+        let read_gates: Vec<NandGate> = (0..words).map(|_| NandGate::new(2)).collect();
+        let read_not_gates: Vec<NotGate> = (0..words).map(|_| NotGate::new()).collect();
+        let latches: Vec<DLatch> = (0..words * width).map(|_| DLatch::new()).collect();
+        let output_gates: Vec<TransmissionGate> = (0..words * width)
+            .map(|_| TransmissionGate::new())
+            .collect();
+        let address = decoder.get_input().to_vec();
+        let data: Vec<Rc<RefCell<Pin>>> = latches[..width]
+            .iter()
+            .map(|l| l.get_data().clone())
+            .collect();
+        let chip_select = decoder.get_enable().clone();
+        let write_enable = not_write_enable_gate.get_input().clone();
+        let output_enable = read_enable_gate.get_input()[0].clone();
+
+        Pin::connect(
+            not_write_enable_gate.get_output(),
+            &read_enable_gate.get_input()[1],
+        );
+        for word in 0..words {
+            let line = &decoder.get_output()[word];
+            let write_gate = &write_gates[word];
+            let read_gate = &read_gates[word];
+            Pin::connect(line, &write_gate.get_input()[0]);
+            Pin::connect(&write_enable, &write_gate.get_input()[1]);
+            Pin::connect(line, &read_gate.get_input()[0]);
+            Pin::connect(read_enable_gate.get_output(), &read_gate.get_input()[1]);
+            Pin::connect(read_gate.get_output(), read_not_gates[word].get_input());
+
+            for bit in 0..width {
+                let latch = &latches[word * width + bit];
+                let output_gate = &output_gates[word * width + bit];
+                Pin::connect(&data[bit], latch.get_data());
+                Pin::connect(write_gate.get_output(), latch.get_enable());
+                Pin::connect(latch.get_output(), output_gate.get_input());
+                Pin::connect(read_not_gates[word].get_output(), output_gate.get_enable());
+                Pin::connect(read_gate.get_output(), output_gate.get_enable_inverted());
+                Pin::connect(output_gate.get_output(), &data[bit]);
+            }
+        }
+
+        Self {
+            decoder,
+            not_write_enable_gate,
+            read_enable_gate,
+            write_gates,
+            read_gates,
+            read_not_gates,
+            latches,
+            output_gates,
+            address,
+            data,
+            chip_select,
+            write_enable,
+            output_enable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, LogicValue, Simulator, TestBus, TestPin};
+
+    // Writes a different value to every address, then reads them all back.
+    fn test_sram(address_bits: usize, width: usize) {
+        let sram = Sram::new(address_bits, width);
+        let mut test_bus_address = TestBus::new(sram.get_address(), DriveValue::HighImpedance);
+        let mut test_bus_data = TestBus::new(sram.get_data(), DriveValue::HighImpedance);
+        let mut test_pin_chip_select = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_write_enable = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_output_enable = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin_chip_select.get_output(), sram.get_chip_select());
+        Pin::connect(test_pin_write_enable.get_output(), sram.get_write_enable());
+        Pin::connect(
+            test_pin_output_enable.get_output(),
+            sram.get_output_enable(),
+        );
+        let mut simulator = Simulator::new(&sram);
+        simulator.settle();
+
+        let words = 1 << address_bits;
+        let value = |address: usize| (address * 5 + 3) % (1 << width);
+        test_pin_chip_select.set_drive(DriveValue::Strong(true));
+        for address in 0..words {
+            test_bus_address.set_value(address);
+            test_bus_data.set_value(value(address));
+            simulator.settle();
+            test_pin_write_enable.set_drive(DriveValue::Strong(true));
+            simulator.settle();
+            test_pin_write_enable.set_drive(DriveValue::Strong(false));
+            simulator.settle();
+        }
+
+        // Release the bus, and read everything back.
+        test_bus_data.set_drive(DriveValue::HighImpedance);
+        test_pin_output_enable.set_drive(DriveValue::Strong(true));
+        for address in 0..words {
+            test_bus_address.set_value(address);
+            simulator.settle();
+            assert_eq!(read_bus(sram.get_data()), Some(value(address)));
+        }
+
+        // Without chip select or output enable, or while writing, nothing drives the bus.
+        for (chip_select, write_enable, output_enable) in [
+            (false, false, true),
+            (true, false, false),
+            (true, true, true),
+        ] {
+            test_pin_chip_select.set_drive(DriveValue::Strong(chip_select));
+            test_pin_write_enable.set_drive(DriveValue::Strong(write_enable));
+            test_pin_output_enable.set_drive(DriveValue::Strong(output_enable));
+            simulator.settle();
+            assert!(sram
+                .get_data()
+                .iter()
+                .all(|pin| pin.borrow().read() == LogicValue::HighImpedance));
+        }
+
+        // Writing without chip select doesn't change anything.
+        test_pin_chip_select.set_drive(DriveValue::Strong(false));
+        test_pin_write_enable.set_drive(DriveValue::Strong(false));
+        test_bus_address.set_value(0);
+        test_bus_data.set_value(!value(0));
+        simulator.settle();
+        test_pin_write_enable.set_drive(DriveValue::Strong(true));
+        simulator.settle();
+        test_pin_write_enable.set_drive(DriveValue::Strong(false));
+        simulator.settle();
+        test_bus_data.set_drive(DriveValue::HighImpedance);
+        test_pin_chip_select.set_drive(DriveValue::Strong(true));
+        simulator.settle();
+        assert_eq!(read_bus(sram.get_data()), Some(value(0)));
+    }
+
+    #[test]
+    fn test_sram_sizes() {
+        test_sram(1, 1);
+        test_sram(1, 4);
+        test_sram(2, 3);
+        test_sram(3, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_sram_address_bits() {
+        Sram::new(0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_sram_width() {
+        Sram::new(1, 0);
+    }
+}
@@ -0,0 +1,85 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An n-to-2^n decoder. While `enable` is high, the output whose index is the unsigned integer on
+/// `input` is high and every other output is low. While `enable` is low, every output is low.
+///
+/// Each output is an AND gate of `enable` and either each input bit or its inverse, depending on
+/// the bits of the output's index.
+#[derive(Device)]
+pub struct Decoder {
+    #[children]
+    not_gates: Vec<NotGate>,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    enable: Rc<RefCell<Pin>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl Decoder {
+    /// Creates a new `Decoder` with the desired number of input bits.
+    pub fn new(bits: usize) -> Self {
+        if bits == 0 {
+            panic!("Decoder bits must be non-zero.")
+        }
+
+        let not_gates: Vec<NotGate> = (0..bits).map(|_| NotGate::new()).collect();
+        let and_gates: Vec<AndGate> = (0..1 << bits).map(|_| AndGate::new(bits + 1)).collect();
+        let input: Vec<Rc<RefCell<Pin>>> =
+            not_gates.iter().map(|g| g.get_input().clone()).collect();
+        let enable = and_gates[0].get_input()[bits].clone();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            and_gates.iter().map(|g| g.get_output().clone()).collect();
+
+        for (index, and_gate) in and_gates.iter().enumerate() {
+            for bit in 0..bits {
+                let literal = match (index >> bit) & 1 {
+                    1 => &input[bit],
+                    _ => not_gates[bit].get_output(),
+                };
+                Pin::connect(literal, &and_gate.get_input()[bit]);
+            }
+            Pin::connect(&enable, &and_gate.get_input()[bits]);
+        }
+
+        Self {
+            not_gates,
+            and_gates,
+            input,
+            enable,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::check_integer_truth_table;
+
+    #[test]
+    fn test_decoder() {
+        for bits in 1..=4 {
+            let decoder = Decoder::new(bits);
+            assert_eq!(decoder.get_output().len(), 1 << bits);
+            let mismatches =
+                check_integer_truth_table(decoder, &["input", "enable"], &["output"], |values| {
+                    vec![values[1] << values[0]]
+                });
+            assert_eq!(mismatches, []);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_decoder() {
+        Decoder::new(0);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::Decoder;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::AndGate;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A demultiplexer, which routes its width-bit input to one of 2^n outputs, where n is the number
+/// of select bits. The outputs are all in `output`, with bit `b` of output `o` at index
+/// `o * width + b`. The output selected by the unsigned integer on `select` follows `input`, and
+/// every other output is low.
+///
+/// A `Decoder` turns `select` into one line per output, and each bit of each output is an AND gate
+/// of its line and the corresponding bit of `input`.
+#[derive(Device)]
+pub struct Demultiplexer {
+    #[child]
+    strong_true: Constant,
+    #[child]
+    decoder: Decoder,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    select: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl Demultiplexer {
+    /// Creates a new `Demultiplexer` with the desired number of select bits, for an input of the
+    /// desired width.
+    pub fn new(select_bits: usize, width: usize) -> Self {
+        if select_bits == 0 {
+            panic!("Demultiplexer select bits must be non-zero.")
+        }
+        if width == 0 {
+            panic!("Demultiplexer width must be non-zero.")
+        }
+
+        let outputs = 1 << select_bits;
+        let strong_true = Constant::new_strong(true);
+        let decoder = Decoder::new(select_bits);
+        let and_gates: Vec<AndGate> = (0..outputs * width).map(|_| AndGate::new(2)).collect();
+        let input: Vec<Rc<RefCell<Pin>>> = and_gates[..width]
+            .iter()
+            .map(|g| g.get_input()[0].clone())
+            .collect();
+        let select = decoder.get_input().to_vec();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            and_gates.iter().map(|g| g.get_output().clone()).collect();
+
+        Pin::connect(strong_true.get_output(), decoder.get_enable());
+        for index in 0..outputs {
+            for bit in 0..width {
+                let and_gate = &and_gates[index * width + bit];
+                Pin::connect(&input[bit], &and_gate.get_input()[0]);
+                Pin::connect(&decoder.get_output()[index], &and_gate.get_input()[1]);
+            }
+        }
+
+        Self {
+            strong_true,
+            decoder,
+            and_gates,
+            input,
+            select,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::check_integer_truth_table;
+
+    #[test]
+    fn test_demultiplexer() {
+        for (select_bits, width) in [(1, 1), (1, 3), (2, 1), (2, 2), (3, 2)] {
+            let demultiplexer = Demultiplexer::new(select_bits, width);
+            assert_eq!(demultiplexer.get_input().len(), width);
+            assert_eq!(demultiplexer.get_output().len(), (1 << select_bits) * width);
+            let mismatches = check_integer_truth_table(
+                demultiplexer,
+                &["input", "select"],
+                &["output"],
+                |values| vec![values[0] << (values[1] * width)],
+            );
+            assert_eq!(mismatches, []);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_demultiplexer_select_bits() {
+        Demultiplexer::new(0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_demultiplexer_width() {
+        Demultiplexer::new(1, 0);
+    }
+}
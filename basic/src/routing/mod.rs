@@ -0,0 +1,9 @@
+mod decoder;
+mod demultiplexer;
+mod multiplexer;
+mod priority_encoder;
+
+pub use decoder::Decoder;
+pub use demultiplexer::Demultiplexer;
+pub use multiplexer::Multiplexer;
+pub use priority_encoder::PriorityEncoder;
@@ -0,0 +1,107 @@
+use crate::Decoder;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A multiplexer, which selects one of 2^n width-bit inputs to pass through to its output, where n
+/// is the number of select bits. The inputs are all in `input`, with bit `b` of input `i` at index
+/// `i * width + b`, and the one passed through to `output` is the unsigned integer on `select`.
+///
+/// A `Decoder` turns `select` into one line per input. Each bit of each input is ANDed with its
+/// line, and an OR gate per output bit combines the results.
+#[derive(Device)]
+pub struct Multiplexer {
+    #[child]
+    strong_true: Constant,
+    #[child]
+    decoder: Decoder,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    select: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl Multiplexer {
+    /// Creates a new `Multiplexer` with the desired number of select bits, for inputs of the
+    /// desired width.
+    pub fn new(select_bits: usize, width: usize) -> Self {
+        if select_bits == 0 {
+            panic!("Multiplexer select bits must be non-zero.")
+        }
+        if width == 0 {
+            panic!("Multiplexer width must be non-zero.")
+        }
+
+        let inputs = 1 << select_bits;
+        let strong_true = Constant::new_strong(true);
+        let decoder = Decoder::new(select_bits);
+        let and_gates: Vec<AndGate> = (0..inputs * width).map(|_| AndGate::new(2)).collect();
+        let or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(inputs)).collect();
+        let input: Vec<Rc<RefCell<Pin>>> =
+            and_gates.iter().map(|g| g.get_input()[0].clone()).collect();
+        let select = decoder.get_input().to_vec();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            or_gates.iter().map(|g| g.get_output().clone()).collect();
+
+        Pin::connect(strong_true.get_output(), decoder.get_enable());
+        for index in 0..inputs {
+            for bit in 0..width {
+                let and_gate = &and_gates[index * width + bit];
+                Pin::connect(&decoder.get_output()[index], &and_gate.get_input()[1]);
+                Pin::connect(and_gate.get_output(), &or_gates[bit].get_input()[index]);
+            }
+        }
+
+        Self {
+            strong_true,
+            decoder,
+            and_gates,
+            or_gates,
+            input,
+            select,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::check_integer_truth_table;
+
+    #[test]
+    fn test_multiplexer() {
+        for (select_bits, width) in [(1, 1), (1, 3), (2, 1), (2, 2), (3, 1)] {
+            let multiplexer = Multiplexer::new(select_bits, width);
+            assert_eq!(multiplexer.get_input().len(), (1 << select_bits) * width);
+            assert_eq!(multiplexer.get_output().len(), width);
+            let mismatches = check_integer_truth_table(
+                multiplexer,
+                &["input", "select"],
+                &["output"],
+                |values| vec![values[0] >> (values[1] * width)],
+            );
+            assert_eq!(mismatches, []);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_multiplexer_select_bits() {
+        Multiplexer::new(0, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_multiplexer_width() {
+        Multiplexer::new(1, 0);
+    }
+}
@@ -0,0 +1,126 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A 2^n-to-n priority encoder. `output` is the index of the highest numbered input that is high,
+/// and `valid` is high if any input is high. If no input is high, `output` is zero.
+///
+/// An AND gate for each input checks that it's high and every higher input is low, so at most one
+/// of them is high. Each output bit is an OR gate of the AND gates of the inputs whose index has
+/// that bit set.
+#[derive(Device)]
+pub struct PriorityEncoder {
+    #[child]
+    strong_false: Constant,
+    #[children]
+    not_gates: Vec<NotGate>,
+    #[children]
+    and_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[child]
+    valid_gate: OrGate,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    valid: Rc<RefCell<Pin>>,
+}
+
+impl PriorityEncoder {
+    /// Creates a new `PriorityEncoder` with the desired number of output bits.
+    pub fn new(bits: usize) -> Self {
+        if bits == 0 {
+            panic!("PriorityEncoder bits must be non-zero.")
+        }
+
+        let inputs = 1 << bits;
+        let strong_false = Constant::new_strong(false);
+        let valid_gate = OrGate::new(inputs);
+        // The highest input has no higher inputs to check, and nothing needs the inverse of the
+        // lowest, so input i is inverted by NOT gate i - 1.
+        let not_gates: Vec<NotGate> = (1..inputs).map(|_| NotGate::new()).collect();
+        let and_gates: Vec<AndGate> = (0..inputs - 1)
+            .map(|index| AndGate::new(inputs - index))
+            .collect();
+        let or_gates: Vec<OrGate> = (0..bits)
+            .map(|_| OrGate::new((inputs / 2).max(2)))
+            .collect();
+        let input = valid_gate.get_input().to_vec();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            or_gates.iter().map(|g| g.get_output().clone()).collect();
+        let valid = valid_gate.get_output().clone();
+
+        for (index, not_gate) in not_gates.iter().enumerate() {
+            Pin::connect(&input[index + 1], not_gate.get_input());
+        }
+        for (index, and_gate) in and_gates.iter().enumerate() {
+            Pin::connect(&input[index], &and_gate.get_input()[0]);
+            for higher in index + 1..inputs {
+                Pin::connect(
+                    not_gates[higher - 1].get_output(),
+                    &and_gate.get_input()[higher - index],
+                );
+            }
+        }
+
+        // An OR gate needs at least two inputs, so with a single output bit the spare input is tied
+        // low.
+        for (bit, or_gate) in or_gates.iter().enumerate() {
+            let highest = (0..inputs).filter(|index| (index >> bit) & 1 == 1);
+            for (or_input, index) in highest.enumerate() {
+                let source = match and_gates.get(index) {
+                    Some(and_gate) => and_gate.get_output(),
+                    None => &input[index],
+                };
+                Pin::connect(source, &or_gate.get_input()[or_input]);
+            }
+            for spare in or_gate.get_input()[inputs / 2..].iter() {
+                Pin::connect(strong_false.get_output(), spare);
+            }
+        }
+
+        Self {
+            strong_false,
+            not_gates,
+            and_gates,
+            or_gates,
+            valid_gate,
+            input,
+            output,
+            valid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::check_integer_truth_table;
+
+    #[test]
+    fn test_priority_encoder() {
+        for bits in 1..=3 {
+            let encoder = PriorityEncoder::new(bits);
+            assert_eq!(encoder.get_input().len(), 1 << bits);
+            assert_eq!(encoder.get_output().len(), bits);
+            let mismatches =
+                check_integer_truth_table(encoder, &["input"], &["output", "valid"], |values| {
+                    match values[0] {
+                        0 => vec![0, 0],
+                        value => vec![value.ilog2() as usize, 1],
+                    }
+                });
+            assert_eq!(mismatches, []);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_priority_encoder() {
+        PriorityEncoder::new(0);
+    }
+}
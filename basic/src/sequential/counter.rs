@@ -0,0 +1,231 @@
+use crate::DFlipFlop;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate, XnorGate, XorGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An n-bit synchronous up/down counter, made from one `DFlipFlop` per bit. On the rising edge of
+/// `clock`:
+///
+/// 1. If `reset` is high, the count is set to zero.
+/// 2. Otherwise, if `load` is high, the count is set to `input`.
+/// 3. Otherwise, if `enable` is high, the count goes up by one if `up` is high, or down by one if
+///    it's low, wrapping around at either end.
+/// 4. Otherwise, the count stays the same.
+///
+/// The count is always available on `output`. Counting up, a bit toggles when every lower bit is
+/// high, and counting down, when every lower bit is low, so each bit has an XNOR gate which checks
+/// whether it matches `up`, and an AND gate of `enable` and the XNOR gates of the lower bits
+/// decides whether it toggles.
+#[derive(Device)]
+pub struct Counter {
+    #[child]
+    not_reset_gate: NotGate,
+    #[child]
+    not_load_gate: NotGate,
+    #[children]
+    match_gates: Vec<XnorGate>,
+    #[children]
+    toggle_gates: Vec<AndGate>,
+    #[children]
+    count_gates: Vec<XorGate>,
+    #[children]
+    load_gates: Vec<AndGate>,
+    #[children]
+    hold_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[children]
+    flip_flops: Vec<DFlipFlop>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    enable: Rc<RefCell<Pin>>,
+    #[pin]
+    up: Rc<RefCell<Pin>>,
+    #[pin]
+    load: Rc<RefCell<Pin>>,
+    #[pin]
+    reset: Rc<RefCell<Pin>>,
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+}
+
+impl Counter {
+    /// Creates a new `Counter` of the desired width. The lowest bit always toggles when enabled, so
+    /// it has no toggle gate, and the toggle gate for bit `b` is at index `b - 1`. Nothing uses the
+    /// match gate of the highest bit, but it means there's somewhere to connect `up` even for a one
+    /// bit counter.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("Counter width must be non-zero.")
+        }
+
+        let not_reset_gate = NotGate::new();
+        let not_load_gate = NotGate::new();
+        let match_gates: Vec<XnorGate> = (0..width).map(|_| XnorGate::new()).collect();
+        let toggle_gates: Vec<AndGate> = (1..width).map(|bit| AndGate::new(bit + 1)).collect();
+        let count_gates: Vec<XorGate> = (0..width).map(|_| XorGate::new()).collect();
+        let load_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(3)).collect();
+        let hold_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(3)).collect();
+        let or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(2)).collect();
+        let flip_flops: Vec<DFlipFlop> = (0..width).map(|_| DFlipFlop::new()).collect();
+        let input: Vec<Rc<RefCell<Pin>>> = load_gates
+            .iter()
+            .map(|g| g.get_input()[2].clone())
+            .collect();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            flip_flops.iter().map(|f| f.get_output().clone()).collect();
+        let enable = count_gates[0].get_b_input().clone();
+        let up = match_gates[0].get_b_input().clone();
+        let load = not_load_gate.get_input().clone();
+        let reset = not_reset_gate.get_input().clone();
+        let clock = flip_flops[0].get_clock().clone();
+
+        for (bit, match_gate) in match_gates.iter().enumerate() {
+            Pin::connect(&output[bit], match_gate.get_a_input());
+            Pin::connect(&up, match_gate.get_b_input());
+        }
+        for (index, toggle_gate) in toggle_gates.iter().enumerate() {
+            Pin::connect(&enable, &toggle_gate.get_input()[0]);
+            for (lower, match_gate) in match_gates[..=index].iter().enumerate() {
+                Pin::connect(match_gate.get_output(), &toggle_gate.get_input()[lower + 1]);
+            }
+            Pin::connect(
+                toggle_gate.get_output(),
+                count_gates[index + 1].get_b_input(),
+            );
+        }
+
+        for bit in 0..width {
+            Pin::connect(&output[bit], count_gates[bit].get_a_input());
+            Pin::connect(not_reset_gate.get_output(), &load_gates[bit].get_input()[0]);
+            Pin::connect(&load, &load_gates[bit].get_input()[1]);
+            Pin::connect(not_reset_gate.get_output(), &hold_gates[bit].get_input()[0]);
+            Pin::connect(not_load_gate.get_output(), &hold_gates[bit].get_input()[1]);
+            Pin::connect(
+                count_gates[bit].get_output(),
+                &hold_gates[bit].get_input()[2],
+            );
+            Pin::connect(load_gates[bit].get_output(), &or_gates[bit].get_input()[0]);
+            Pin::connect(hold_gates[bit].get_output(), &or_gates[bit].get_input()[1]);
+            Pin::connect(or_gates[bit].get_output(), flip_flops[bit].get_data());
+            Pin::connect(&clock, flip_flops[bit].get_clock());
+        }
+
+        Self {
+            not_reset_gate,
+            not_load_gate,
+            match_gates,
+            toggle_gates,
+            count_gates,
+            load_gates,
+            hold_gates,
+            or_gates,
+            flip_flops,
+            input,
+            output,
+            enable,
+            up,
+            load,
+            reset,
+            clock,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, Simulator, TestBus, TestPin};
+
+    // Each step is (enable, up, load, reset, input), and the counter is clocked after each one.
+    // The expected count is calculated in the same order of priority as the counter.
+    fn test_counter_steps(width: usize, steps: &[(bool, bool, bool, bool, usize)]) {
+        let counter = Counter::new(width);
+        let mask = (1 << width) - 1;
+        let mut test_pin_enable = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_up = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_load = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_reset = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+        let mut test_bus_input = TestBus::new(counter.get_input(), DriveValue::Strong(false));
+        Pin::connect(test_pin_enable.get_output(), counter.get_enable());
+        Pin::connect(test_pin_up.get_output(), counter.get_up());
+        Pin::connect(test_pin_load.get_output(), counter.get_load());
+        Pin::connect(test_pin_reset.get_output(), counter.get_reset());
+        Pin::connect(test_pin_clock.get_output(), counter.get_clock());
+        let mut simulator = Simulator::new(&counter);
+        simulator.settle();
+        assert_eq!(read_bus(counter.get_output()), Some(0));
+
+        let mut expected = 0;
+        for (enable, up, load, reset, input) in steps.iter().copied() {
+            test_pin_enable.set_drive(DriveValue::Strong(enable));
+            test_pin_up.set_drive(DriveValue::Strong(up));
+            test_pin_load.set_drive(DriveValue::Strong(load));
+            test_pin_reset.set_drive(DriveValue::Strong(reset));
+            test_bus_input.set_value(input);
+            simulator.settle();
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            simulator.settle();
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            simulator.settle();
+
+            expected = match (enable, up, load, reset) {
+                (_, _, _, true) => 0,
+                (_, _, true, _) => input,
+                (true, true, _, _) => (expected + 1) & mask,
+                (true, false, _, _) => expected.wrapping_sub(1) & mask,
+                _ => expected,
+            };
+            assert_eq!(read_bus(counter.get_output()), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_counter_up_down() {
+        for width in 1..=4 {
+            // Count all the way up and around, then all the way down and around.
+            let cycles = (1 << width) + 1;
+            let up = (0..cycles).map(|_| (true, true, false, false, 0));
+            let down = (0..cycles).map(|_| (true, false, false, false, 0));
+            test_counter_steps(width, &up.chain(down).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_counter_controls() {
+        test_counter_steps(
+            3,
+            &[
+                // Counting only happens while enabled.
+                (true, true, false, false, 0),
+                (false, true, false, false, 0),
+                (true, true, false, false, 0),
+                (false, false, false, false, 0),
+                // Load takes priority over counting.
+                (true, true, true, false, 5),
+                (true, true, false, false, 2),
+                (false, false, true, false, 3),
+                (true, false, false, false, 0),
+                // Reset takes priority over everything.
+                (true, true, true, true, 7),
+                (true, false, false, false, 0),
+                (false, false, false, true, 0),
+                (true, false, true, false, 1),
+                (true, false, false, false, 1),
+                (true, false, false, false, 1),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_counter() {
+        Counter::new(0);
+    }
+}
@@ -0,0 +1,9 @@
+mod counter;
+mod register;
+mod register_file;
+mod shift_register;
+
+pub use counter::Counter;
+pub use register::Register;
+pub use register_file::RegisterFile;
+pub use shift_register::{ShiftRegister, ShiftRegisterMode};
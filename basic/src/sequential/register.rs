@@ -0,0 +1,127 @@
+use crate::DFlipFlop;
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin};
+use gate::{AndGate, NotGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// An n-bit register, made from one `DFlipFlop` per bit. On the rising edge of `clock` the register
+/// stores `input` if `load` is high, and keeps its current value otherwise. The stored value is
+/// always available on `output`.
+///
+/// Each flip-flop is fed by a pair of AND gates and an OR gate, which select either its input or
+/// its own output depending on `load`, so `load` has the same setup and hold times as the data.
+#[derive(Device)]
+pub struct Register {
+    #[child]
+    not_gate: NotGate,
+    #[children]
+    load_gates: Vec<AndGate>,
+    #[children]
+    hold_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[children]
+    flip_flops: Vec<DFlipFlop>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    load: Rc<RefCell<Pin>>,
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+}
+
+impl Register {
+    /// Creates a new `Register` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("Register width must be non-zero.")
+        }
+
+        let not_gate = NotGate::new();
+        let load_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let hold_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(2)).collect();
+        let flip_flops: Vec<DFlipFlop> = (0..width).map(|_| DFlipFlop::new()).collect();
+        let input: Vec<Rc<RefCell<Pin>>> = load_gates
+            .iter()
+            .map(|g| g.get_input()[0].clone())
+            .collect();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            flip_flops.iter().map(|f| f.get_output().clone()).collect();
+        let load = not_gate.get_input().clone();
+        let clock = flip_flops[0].get_clock().clone();
+
+        for bit in 0..width {
+            Pin::connect(&load, &load_gates[bit].get_input()[1]);
+            Pin::connect(not_gate.get_output(), &hold_gates[bit].get_input()[0]);
+            Pin::connect(
+                flip_flops[bit].get_output(),
+                &hold_gates[bit].get_input()[1],
+            );
+            Pin::connect(load_gates[bit].get_output(), &or_gates[bit].get_input()[0]);
+            Pin::connect(hold_gates[bit].get_output(), &or_gates[bit].get_input()[1]);
+            Pin::connect(or_gates[bit].get_output(), flip_flops[bit].get_data());
+            Pin::connect(&clock, flip_flops[bit].get_clock());
+        }
+
+        Self {
+            not_gate,
+            load_gates,
+            hold_gates,
+            or_gates,
+            flip_flops,
+            input,
+            output,
+            load,
+            clock,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, settle, DriveValue, TestBus, TestPin};
+
+    #[test]
+    fn test_register() {
+        let width = 4;
+        let mut register = Register::new(width);
+        let mut test_bus_input = TestBus::new(register.get_input(), DriveValue::Strong(false));
+        let mut test_pin_load = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin_load.get_output(), register.get_load());
+        Pin::connect(test_pin_clock.get_output(), register.get_clock());
+
+        settle(&mut register);
+        assert_eq!(read_bus(register.get_output()), Some(0));
+
+        let mut expected = 0;
+        for (value, load) in [(5, true), (10, false), (10, true), (15, true), (0, false)] {
+            test_bus_input.set_value(value);
+            test_pin_load.set_drive(DriveValue::Strong(load));
+            settle(&mut register);
+            assert_eq!(read_bus(register.get_output()), Some(expected));
+
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut register);
+            if load {
+                expected = value;
+            }
+            assert_eq!(read_bus(register.get_output()), Some(expected));
+
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut register);
+            assert_eq!(read_bus(register.get_output()), Some(expected));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_register() {
+        Register::new(0);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::Register;
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, NandGate, NotGate, TriStateBufferGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A bank of `Register`s with one write port and two read ports, e.g. for the general purpose
+/// registers of a CPU.
+///
+/// On the rising edge of `clock`, if `write_enable` is high, the register selected by
+/// `write_address` stores `write_data`. The registers selected by `read_address_a` and
+/// `read_address_b` are always available on `read_data_a` and `read_data_b`, so reading a register
+/// that is being written gives the old value until the clock edge.
+///
+/// Addresses have enough bits to select every register, and at least one. If the number of
+/// registers isn't a power of two, reading an address with no register gives high impedance, and
+/// writing to it does nothing.
+#[derive(Device)]
+pub struct RegisterFile {
+    #[child]
+    strong_true: Constant,
+    #[children]
+    registers: Vec<Register>,
+    #[children]
+    write_address_not_gates: Vec<NotGate>,
+    #[children]
+    write_select_gates: Vec<AndGate>,
+    #[child]
+    read_port_a: ReadPort,
+    #[child]
+    read_port_b: ReadPort,
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+    #[pin]
+    write_enable: Rc<RefCell<Pin>>,
+    #[pins]
+    write_address: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    write_data: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    read_address_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    read_data_a: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    read_address_b: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    read_data_b: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl RegisterFile {
+    /// Creates a new `RegisterFile` with the desired number of registers of the desired width.
+    pub fn new(count: usize, width: usize) -> Self {
+        if count == 0 {
+            panic!("RegisterFile count must be non-zero.")
+        }
+        if width == 0 {
+            panic!("RegisterFile width must be non-zero.")
+        }
+
+        let address_bits = (usize::BITS - (count - 1).leading_zeros()).max(1) as usize;
+        let strong_true = Constant::new_strong(true);
+        let registers: Vec<Register> = (0..count).map(|_| Register::new(width)).collect();
+        let write_address_not_gates: Vec<NotGate> =
+            (0..address_bits).map(|_| NotGate::new()).collect();
+        let write_select_gates: Vec<AndGate> =
+            (0..count).map(|_| AndGate::new(address_bits + 1)).collect();
+        let read_port_a = ReadPort::new(&registers, address_bits, &strong_true);
+        let read_port_b = ReadPort::new(&registers, address_bits, &strong_true);
+        let clock = registers[0].get_clock().clone();
+        let write_enable = write_select_gates[0].get_input()[address_bits].clone();
+        let write_address: Vec<Rc<RefCell<Pin>>> = write_address_not_gates
+            .iter()
+            .map(|g| g.get_input().clone())
+            .collect();
+        let write_data = registers[0].get_input().to_vec();
+        let read_address_a = read_port_a.get_address().to_vec();
+        let read_data_a = read_port_a.get_data().to_vec();
+        let read_address_b = read_port_b.get_address().to_vec();
+        let read_data_b = read_port_b.get_data().to_vec();
+
+        // Each register is loaded when the write address matches its index and writing is enabled.
+        for (index, register) in registers.iter().enumerate() {
+            let select_gate = &write_select_gates[index];
+            for bit in 0..address_bits {
+                let literal = match (index >> bit) & 1 {
+                    1 => &write_address[bit],
+                    _ => write_address_not_gates[bit].get_output(),
+                };
+                Pin::connect(literal, &select_gate.get_input()[bit]);
+            }
+            Pin::connect(&write_enable, &select_gate.get_input()[address_bits]);
+            Pin::connect(select_gate.get_output(), register.get_load());
+            Pin::connect(&clock, register.get_clock());
+            for (write_pin, input_pin) in write_data.iter().zip(register.get_input()) {
+                Pin::connect(write_pin, input_pin);
+            }
+        }
+
+        Self {
+            strong_true,
+            registers,
+            write_address_not_gates,
+            write_select_gates,
+            read_port_a,
+            read_port_b,
+            clock,
+            write_enable,
+            write_address,
+            write_data,
+            read_address_a,
+            read_data_a,
+            read_address_b,
+            read_data_b,
+        }
+    }
+}
+
+/// A read port of a `RegisterFile`. The outputs of every register are connected to `data` through
+/// tri-state buffers, and only the buffers of the register selected by `address` are enabled.
+#[derive(Device)]
+struct ReadPort {
+    #[children]
+    address_not_gates: Vec<NotGate>,
+    #[children]
+    select_gates: Vec<NandGate>,
+    #[children]
+    buffers: Vec<TriStateBufferGate>,
+    #[pins]
+    address: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    data: Vec<Rc<RefCell<Pin>>>,
+}
+
+impl ReadPort {
+    /// Creates a new read port for the given registers. The buffers for register `r` and bit `b`
+    /// are at index `r * width + b`.
+    fn new(registers: &[Register], address_bits: usize, strong_true: &Constant) -> Self {
+        let width = registers[0].get_output().len();
+        let address_not_gates: Vec<NotGate> = (0..address_bits).map(|_| NotGate::new()).collect();
+        let select_gates: Vec<NandGate> = registers
+            .iter()
+            .map(|_| NandGate::new(address_bits.max(2)))
+            .collect();
+        let buffers: Vec<TriStateBufferGate> = (0..registers.len() * width)
+            .map(|_| TriStateBufferGate::new())
+            .collect();
+        let address: Vec<Rc<RefCell<Pin>>> = address_not_gates
+            .iter()
+            .map(|g| g.get_input().clone())
+            .collect();
+        let data: Vec<Rc<RefCell<Pin>>> = buffers[..width]
+            .iter()
+            .map(|b| b.get_output().clone())
+            .collect();
+
+        // The select gate for each register goes low, enabling its buffers, when the address
+        // matches its index. A NAND gate needs at least two inputs, so any spare input is tied
+        // high.
+        for (index, register) in registers.iter().enumerate() {
+            let select_gate = &select_gates[index];
+            for bit in 0..address_bits {
+                let literal = match (index >> bit) & 1 {
+                    1 => &address[bit],
+                    _ => address_not_gates[bit].get_output(),
+                };
+                Pin::connect(literal, &select_gate.get_input()[bit]);
+            }
+            for spare in select_gate.get_input()[address_bits..].iter() {
+                Pin::connect(strong_true.get_output(), spare);
+            }
+
+            for bit in 0..width {
+                let buffer = &buffers[index * width + bit];
+                Pin::connect(select_gate.get_output(), buffer.get_enable());
+                Pin::connect(&register.get_output()[bit], buffer.get_input());
+                Pin::connect(buffer.get_output(), &data[bit]);
+            }
+        }
+
+        Self {
+            address_not_gates,
+            select_gates,
+            buffers,
+            address,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, settle, DriveValue, LogicValue, TestBus, TestPin};
+
+    // Writes a different value to every address, then reads every pair of addresses.
+    fn test_register_file(count: usize, width: usize) {
+        let mut register_file = RegisterFile::new(count, width);
+        let addresses = 1 << register_file.get_write_address().len();
+        let mut test_pin_clock = TestPin::new(DriveValue::Strong(false));
+        let mut test_pin_write_enable = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin_clock.get_output(), register_file.get_clock());
+        Pin::connect(
+            test_pin_write_enable.get_output(),
+            register_file.get_write_enable(),
+        );
+        let mut test_bus_write_address =
+            TestBus::new(register_file.get_write_address(), DriveValue::Strong(false));
+        let mut test_bus_write_data =
+            TestBus::new(register_file.get_write_data(), DriveValue::Strong(false));
+        let mut test_bus_read_address_a = TestBus::new(
+            register_file.get_read_address_a(),
+            DriveValue::Strong(false),
+        );
+        let mut test_bus_read_address_b = TestBus::new(
+            register_file.get_read_address_b(),
+            DriveValue::Strong(false),
+        );
+        settle(&mut register_file);
+
+        let value = |address: usize| (address * 5 + 3) % (1 << width);
+        for address in 0..addresses {
+            test_bus_write_address.set_value(address);
+            test_bus_write_data.set_value(value(address));
+            test_pin_write_enable.set_drive(DriveValue::Strong(true));
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut register_file);
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut register_file);
+        }
+
+        // Clocking with writes disabled doesn't change anything.
+        test_bus_write_data.set_value(0);
+        test_pin_write_enable.set_drive(DriveValue::Strong(false));
+        for address in 0..addresses {
+            test_bus_write_address.set_value(address);
+            test_pin_clock.set_drive(DriveValue::Strong(true));
+            settle(&mut register_file);
+            test_pin_clock.set_drive(DriveValue::Strong(false));
+            settle(&mut register_file);
+        }
+
+        // An address without a register leaves the read data floating.
+        let check = |data: &[Rc<RefCell<Pin>>], address: usize| match address < count {
+            true => assert_eq!(read_bus(data), Some(value(address))),
+            false => assert!(data
+                .iter()
+                .all(|pin| pin.borrow().read() == LogicValue::HighImpedance)),
+        };
+        for address_a in 0..addresses {
+            for address_b in 0..addresses {
+                test_bus_read_address_a.set_value(address_a);
+                test_bus_read_address_b.set_value(address_b);
+                settle(&mut register_file);
+                check(register_file.get_read_data_a(), address_a);
+                check(register_file.get_read_data_b(), address_b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_file_sizes() {
+        test_register_file(1, 2);
+        test_register_file(2, 1);
+        test_register_file(3, 3);
+        test_register_file(4, 2);
+    }
+
+    #[test]
+    fn test_register_file_address_bits() {
+        for (count, address_bits) in [(1, 1), (2, 1), (3, 2), (4, 2), (5, 3), (32, 5)] {
+            let register_file = RegisterFile::new(count, 1);
+            assert_eq!(register_file.get_write_address().len(), address_bits);
+            assert_eq!(register_file.get_read_address_a().len(), address_bits);
+            assert_eq!(register_file.get_read_address_b().len(), address_bits);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_register_file() {
+        RegisterFile::new(0, 1);
+    }
+}
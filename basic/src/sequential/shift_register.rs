@@ -0,0 +1,306 @@
+use crate::{DFlipFlop, Decoder};
+use device_derive::Device;
+use foundation::{AnyDevice, Constant, Device, DeviceContainer, Pin};
+use gate::{AndGate, OrGate};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The modes of a `ShiftRegister`. The value of each is the unsigned integer to put on the `mode`
+/// pins to select it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShiftRegisterMode {
+    /// Keep the stored value.
+    Hold = 0,
+    /// Shift every bit one place towards bit zero, shifting `serial_input_right` into the highest
+    /// bit.
+    ShiftRight = 1,
+    /// Shift every bit one place away from bit zero, shifting `serial_input_left` into bit zero.
+    ShiftLeft = 2,
+    /// Store `input`.
+    Load = 3,
+}
+
+/// An n-bit bidirectional shift register with parallel load, made from one `DFlipFlop` per bit.
+/// On the rising edge of `clock` it does whatever the `ShiftRegisterMode` on `mode` says. The
+/// stored value is always available on `output`.
+///
+/// Shifting in through `serial_input_left` and reading `output` makes it a serial-in/parallel-out
+/// register, and loading `input` and shifting out through `serial_output_left` (the highest bit)
+/// makes it a parallel-in/serial-out register. `serial_output_right` is bit zero, which is the bit
+/// shifted out when shifting right.
+///
+/// A `Decoder` turns `mode` into one line per mode, and each flip-flop is fed by an AND gate per
+/// mode, which passes the bit that mode would store, and an OR gate that combines them.
+#[derive(Device)]
+pub struct ShiftRegister {
+    #[child]
+    strong_true: Constant,
+    #[child]
+    decoder: Decoder,
+    #[children]
+    hold_gates: Vec<AndGate>,
+    #[children]
+    shift_right_gates: Vec<AndGate>,
+    #[children]
+    shift_left_gates: Vec<AndGate>,
+    #[children]
+    load_gates: Vec<AndGate>,
+    #[children]
+    or_gates: Vec<OrGate>,
+    #[children]
+    flip_flops: Vec<DFlipFlop>,
+    #[pins]
+    input: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    output: Vec<Rc<RefCell<Pin>>>,
+    #[pins]
+    mode: Vec<Rc<RefCell<Pin>>>,
+    #[pin]
+    serial_input_left: Rc<RefCell<Pin>>,
+    #[pin]
+    serial_input_right: Rc<RefCell<Pin>>,
+    #[pin]
+    serial_output_left: Rc<RefCell<Pin>>,
+    #[pin]
+    serial_output_right: Rc<RefCell<Pin>>,
+    #[pin]
+    clock: Rc<RefCell<Pin>>,
+}
+
+impl ShiftRegister {
+    /// Creates a new `ShiftRegister` of the desired width.
+    pub fn new(width: usize) -> Self {
+        if width == 0 {
+            panic!("ShiftRegister width must be non-zero.")
+        }
+
+        let strong_true = Constant::new_strong(true);
+        let decoder = Decoder::new(2);
+        let hold_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let shift_right_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let shift_left_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let load_gates: Vec<AndGate> = (0..width).map(|_| AndGate::new(2)).collect();
+        let or_gates: Vec<OrGate> = (0..width).map(|_| OrGate::new(4)).collect();
+        let flip_flops: Vec<DFlipFlop> = (0..width).map(|_| DFlipFlop::new()).collect();
+        let input: Vec<Rc<RefCell<Pin>>> = load_gates
+            .iter()
+            .map(|g| g.get_input()[1].clone())
+            .collect();
+        let output: Vec<Rc<RefCell<Pin>>> =
+            flip_flops.iter().map(|f| f.get_output().clone()).collect();
+        let mode = decoder.get_input().to_vec();
+        let serial_input_left = shift_left_gates[0].get_input()[1].clone();
+        let serial_input_right = shift_right_gates[width - 1].get_input()[1].clone();
+        let serial_output_left = output[width - 1].clone();
+        let serial_output_right = output[0].clone();
+        let clock = flip_flops[0].get_clock().clone();
+
+        Pin::connect(strong_true.get_output(), decoder.get_enable());
+        let line = |mode: ShiftRegisterMode| &decoder.get_output()[mode as usize];
+        for bit in 0..width {
+            let from_left = match bit {
+                0 => &serial_input_left,
+                _ => &output[bit - 1],
+            };
+            let from_right = output.get(bit + 1).unwrap_or(&serial_input_right);
+            for (mode, gates, source) in [
+                (ShiftRegisterMode::Hold, &hold_gates, &output[bit]),
+                (
+                    ShiftRegisterMode::ShiftRight,
+                    &shift_right_gates,
+                    from_right,
+                ),
+                (ShiftRegisterMode::ShiftLeft, &shift_left_gates, from_left),
+                (ShiftRegisterMode::Load, &load_gates, &input[bit]),
+            ] {
+                Pin::connect(line(mode), &gates[bit].get_input()[0]);
+                Pin::connect(source, &gates[bit].get_input()[1]);
+                Pin::connect(
+                    gates[bit].get_output(),
+                    &or_gates[bit].get_input()[mode as usize],
+                );
+            }
+            Pin::connect(or_gates[bit].get_output(), flip_flops[bit].get_data());
+            Pin::connect(&clock, flip_flops[bit].get_clock());
+        }
+
+        Self {
+            strong_true,
+            decoder,
+            hold_gates,
+            shift_right_gates,
+            shift_left_gates,
+            load_gates,
+            or_gates,
+            flip_flops,
+            input,
+            output,
+            mode,
+            serial_input_left,
+            serial_input_right,
+            serial_output_left,
+            serial_output_right,
+            clock,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{read_bus, DriveValue, LogicValue, Simulator, TestBus, TestPin};
+
+    // Test pins connected to every input of a shift register.
+    struct Stimulus {
+        input: TestBus,
+        mode: TestBus,
+        serial_input_left: TestPin,
+        serial_input_right: TestPin,
+        clock: TestPin,
+    }
+
+    impl Stimulus {
+        fn new(shift_register: &ShiftRegister) -> Self {
+            let new_test_pin = || TestPin::new(DriveValue::Strong(false));
+            let stimulus = Self {
+                input: TestBus::new(shift_register.get_input(), DriveValue::Strong(false)),
+                mode: TestBus::new(shift_register.get_mode(), DriveValue::Strong(false)),
+                serial_input_left: new_test_pin(),
+                serial_input_right: new_test_pin(),
+                clock: new_test_pin(),
+            };
+            Pin::connect(
+                stimulus.serial_input_left.get_output(),
+                shift_register.get_serial_input_left(),
+            );
+            Pin::connect(
+                stimulus.serial_input_right.get_output(),
+                shift_register.get_serial_input_right(),
+            );
+            Pin::connect(stimulus.clock.get_output(), shift_register.get_clock());
+            stimulus
+        }
+
+        // Sets the inputs, and then clocks the shift register.
+        fn step(
+            &mut self,
+            simulator: &mut Simulator,
+            mode: ShiftRegisterMode,
+            input: usize,
+            serial_input: bool,
+        ) {
+            self.mode.set_value(mode as usize);
+            self.input.set_value(input);
+            self.serial_input_left
+                .set_drive(DriveValue::Strong(serial_input));
+            self.serial_input_right
+                .set_drive(DriveValue::Strong(serial_input));
+            simulator.settle();
+            self.clock.set_drive(DriveValue::Strong(true));
+            simulator.settle();
+            self.clock.set_drive(DriveValue::Strong(false));
+            simulator.settle();
+        }
+    }
+
+    #[test]
+    fn test_shift_register_serial_in_parallel_out() {
+        let shift_register = ShiftRegister::new(4);
+        let mut stimulus = Stimulus::new(&shift_register);
+        let mut simulator = Simulator::new(&shift_register);
+        simulator.settle();
+        assert_eq!(read_bus(shift_register.get_output()), Some(0));
+
+        // Shift in 0b1011, highest bit first.
+        let mut expected = 0;
+        for bit in [true, false, true, true] {
+            stimulus.step(&mut simulator, ShiftRegisterMode::ShiftLeft, 0, bit);
+            expected = (expected << 1 | bit as usize) & 0b1111;
+            assert_eq!(read_bus(shift_register.get_output()), Some(expected));
+        }
+        assert_eq!(expected, 0b1011);
+
+        // Holding keeps the value whatever the other inputs are.
+        stimulus.step(&mut simulator, ShiftRegisterMode::Hold, 0b0100, false);
+        assert_eq!(read_bus(shift_register.get_output()), Some(0b1011));
+    }
+
+    #[test]
+    fn test_shift_register_parallel_in_serial_out() {
+        let shift_register = ShiftRegister::new(4);
+        let mut stimulus = Stimulus::new(&shift_register);
+        let mut simulator = Simulator::new(&shift_register);
+        simulator.settle();
+
+        for value in [0b1101, 0b0110] {
+            stimulus.step(&mut simulator, ShiftRegisterMode::Load, value, false);
+            assert_eq!(read_bus(shift_register.get_output()), Some(value));
+
+            // Shifting left, the highest bit comes out first.
+            for bit in (0..4).rev() {
+                let expected = LogicValue::Driven((value >> bit) & 1 == 1);
+                assert_eq!(
+                    shift_register.get_serial_output_left().borrow().read(),
+                    expected
+                );
+                stimulus.step(&mut simulator, ShiftRegisterMode::ShiftLeft, 0, false);
+            }
+            assert_eq!(read_bus(shift_register.get_output()), Some(0));
+
+            // Shifting right, the lowest bit comes out first.
+            stimulus.step(&mut simulator, ShiftRegisterMode::Load, value, false);
+            for bit in 0..4 {
+                let expected = LogicValue::Driven((value >> bit) & 1 == 1);
+                assert_eq!(
+                    shift_register.get_serial_output_right().borrow().read(),
+                    expected
+                );
+                stimulus.step(&mut simulator, ShiftRegisterMode::ShiftRight, 0, false);
+            }
+            assert_eq!(read_bus(shift_register.get_output()), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_shift_register_bidirectional() {
+        for width in 1..=4 {
+            let shift_register = ShiftRegister::new(width);
+            let mask = (1 << width) - 1;
+            let mut stimulus = Stimulus::new(&shift_register);
+            let mut simulator = Simulator::new(&shift_register);
+            simulator.settle();
+
+            // Each step is (mode, input, serial input).
+            let steps = [
+                (ShiftRegisterMode::Load, 0b1010, false),
+                (ShiftRegisterMode::ShiftLeft, 0, true),
+                (ShiftRegisterMode::ShiftRight, 0, true),
+                (ShiftRegisterMode::ShiftRight, 0, false),
+                (ShiftRegisterMode::Hold, 0, true),
+                (ShiftRegisterMode::ShiftLeft, 0, false),
+                (ShiftRegisterMode::ShiftLeft, 0, true),
+                (ShiftRegisterMode::Load, 0b0101, true),
+                (ShiftRegisterMode::ShiftRight, 0, true),
+            ];
+            let mut expected = 0;
+            for (mode, input, serial_input) in steps {
+                stimulus.step(&mut simulator, mode, input & mask, serial_input);
+                expected = match mode {
+                    ShiftRegisterMode::Hold => expected,
+                    ShiftRegisterMode::ShiftRight => {
+                        expected >> 1 | (serial_input as usize) << (width - 1)
+                    }
+                    ShiftRegisterMode::ShiftLeft => (expected << 1 | serial_input as usize) & mask,
+                    ShiftRegisterMode::Load => input & mask,
+                };
+                assert_eq!(read_bus(shift_register.get_output()), Some(expected));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_shift_register() {
+        ShiftRegister::new(0);
+    }
+}
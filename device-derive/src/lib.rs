@@ -25,7 +25,11 @@ use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Field, Fields};
 /// allows all `Device` fields to be private
 ///
 /// Nothing is done to fields without attributes.
-#[proc_macro_derive(Device, attributes(pin, pins, child, children))]
+///
+/// If the `struct` itself is labeled with the `behavioral` attribute, then the `behavioral`
+/// function of the `Device` trait returns the `struct`, which must also implement the
+/// `foundation::Behavioral` trait.
+#[proc_macro_derive(Device, attributes(pin, pins, child, children, behavioral))]
 pub fn derive_device(input_token_stream: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input_token_stream as DeriveInput);
     let struct_identifier = &input.ident;
@@ -38,6 +42,7 @@ pub fn derive_device(input_token_stream: TokenStream) -> TokenStream {
         let pin_getters = make_pin_getters(&pin_fields, &pins_fields);
         let pins_implementation = make_pins_implementation(&pin_fields, &pins_fields);
         let children_implementation = make_children_implementation(&child_fields, &children_fields);
+        let behavioral_implementation = make_behavioral_implementation(&input);
 
         quote! {
             impl #struct_identifier {
@@ -48,6 +53,7 @@ pub fn derive_device(input_token_stream: TokenStream) -> TokenStream {
             impl Device for #struct_identifier {
                 #pins_implementation
                 #children_implementation
+                #behavioral_implementation
 
                 fn type_name(&self) -> String {
                     stringify!(#struct_identifier).to_string()
@@ -68,6 +74,24 @@ fn get_fields_with_attribute<'a>(fields: &'a Fields, ident: &str) -> Vec<&'a Fie
         .collect()
 }
 
+/// Creates the implementation of the `behavioral` function of the `Device` trait, if the `struct`
+/// has the `behavioral` attribute. Otherwise the default implementation is used.
+fn make_behavioral_implementation(input: &DeriveInput) -> TokenStream2 {
+    if input
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("behavioral"))
+    {
+        quote! {
+            fn behavioral(&self) -> Option<&dyn Behavioral> {
+                Some(self)
+            }
+        }
+    } else {
+        quote! {}
+    }
+}
+
 /// Creates the implementation of the `pins` and `pins_mut` functions of the `Device` trait.
 fn make_pins_implementation(pin_fields: &[&Field], pins_fields: &[&Field]) -> TokenStream2 {
     if !pin_fields.is_empty() || !pins_fields.is_empty() {
@@ -0,0 +1,432 @@
+use crate::{AnyDevice, DriveValue, LogicValue, Netlist, Pin, Simulator, TestPin};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// Options controlling how [`check_equivalence`] chooses its stimulus.
+pub struct EquivalenceOptions {
+    /// Every combination of inputs is checked if there are at most this many input `Pin`s,
+    /// otherwise `random_vectors` random combinations are checked. This is capped so the number of
+    /// combinations fits in a `usize`.
+    pub exhaustive_inputs: usize,
+
+    /// The number of random combinations of inputs to check when there are too many input `Pin`s
+    /// to check them all.
+    pub random_vectors: usize,
+
+    /// The seed for the random combinations, so a failing check can be repeated.
+    pub seed: u64,
+
+    /// The number of ticks each `Device` is given to settle after the inputs change.
+    pub settle_limit: usize,
+}
+
+impl Default for EquivalenceOptions {
+    fn default() -> Self {
+        Self {
+            exhaustive_inputs: 12,
+            random_vectors: 1000,
+            seed: 0x5eed,
+            settle_limit: 10_000,
+        }
+    }
+}
+
+/// The first difference found by [`check_equivalence`] between two `Device`s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The name and value of every input `Pin` when the outputs diverged.
+    inputs: Vec<(String, bool)>,
+
+    /// The name of the first output `Pin` whose value differs.
+    pin: String,
+
+    /// The value of the output `Pin` on the first `Device`.
+    value_a: LogicValue,
+
+    /// The value of the output `Pin` on the second `Device`.
+    value_b: LogicValue,
+}
+
+impl Divergence {
+    /// Gets the name and value of every input `Pin` when the outputs diverged, in the order the
+    /// inputs were given to [`check_equivalence`].
+    pub fn get_inputs(&self) -> &[(String, bool)] {
+        &self.inputs
+    }
+
+    /// Gets the name of the first output `Pin` whose value differs, e.g. `sum[3]`.
+    pub fn get_pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// Gets the value of the output `Pin` on the first `Device`.
+    pub fn get_value_a(&self) -> LogicValue {
+        self.value_a
+    }
+
+    /// Gets the value of the output `Pin` on the second `Device`.
+    pub fn get_value_b(&self) -> LogicValue {
+        self.value_b
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, *value as u8))
+            .collect();
+        write!(
+            formatter,
+            "{} is {:?} but should be {:?} when {}",
+            self.pin,
+            self.value_b,
+            self.value_a,
+            inputs.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Checks that two `Device`s with the same `Pin` names behave the same way.
+///
+/// `inputs` are the names of the input `Pin`s, or groups of `Pin`s such as `input_a`, and every
+/// other `Pin` of the root devices is an output. A `TestPin` is connected to each input of both
+/// `Device`s, and the same strong values are driven onto them. After both `Device`s settle, the
+/// values of the outputs are compared. They must be exactly the same, so an `Error` only matches
+/// an `Error`, and `HighImpedance` only matches `HighImpedance`. Every combination of inputs is
+/// checked if there are few enough of them, otherwise random combinations are checked, see
+/// [`EquivalenceOptions`]. The exhaustive combinations count up, with the first input being the
+/// least significant bit, and are applied one after the other without resetting the `Device`s, so
+/// this is mostly useful for combinational logic.
+///
+/// The `Device`s are consumed since the `TestPin`s are dropped at the end, after which the
+/// `Device`s can't be used. The first `Device` is treated as the reference, e.g. a behavioral
+/// model, when describing a `Divergence`.
+///
+/// Returns the number of combinations checked, or the first `Divergence`.
+///
+/// Panics if the `Device`s don't have the same `Pin` names, if one of the `inputs` doesn't name a
+/// `Pin`, or if a `Device` doesn't settle.
+pub fn check_equivalence(
+    device_a: impl AnyDevice,
+    device_b: impl AnyDevice,
+    inputs: &[&str],
+    options: &EquivalenceOptions,
+) -> Result<usize, Divergence> {
+    let (input_names, output_names) = split_pins(&device_a, inputs);
+    let (_, output_names_b) = split_pins(&device_b, inputs);
+    if output_names != output_names_b {
+        panic!(
+            "Devices have different outputs, {:?} and {:?}.",
+            output_names, output_names_b
+        )
+    }
+
+    let pins_a = root_pins(&device_a);
+    let pins_b = root_pins(&device_b);
+    let connect = |pins: &HashMap<String, Rc<RefCell<Pin>>>| -> Vec<TestPin> {
+        input_names
+            .iter()
+            .map(|name| {
+                let test_pin = TestPin::new(DriveValue::HighImpedance);
+                Pin::connect(test_pin.get_output(), &pins[name]);
+                test_pin
+            })
+            .collect()
+    };
+    let mut test_pins_a = connect(&pins_a);
+    let mut test_pins_b = connect(&pins_b);
+    let mut simulator_a = Simulator::new(&device_a);
+    let mut simulator_b = Simulator::new(&device_b);
+
+    let max_exhaustive_inputs = options.exhaustive_inputs.min(usize::BITS as usize - 1);
+    let exhaustive = input_names.len() <= max_exhaustive_inputs;
+    let vectors = if exhaustive {
+        1 << input_names.len()
+    } else {
+        options.random_vectors
+    };
+    let mut random = Random(options.seed.max(1));
+    for vector in 0..vectors {
+        let values: Vec<bool> = (0..input_names.len())
+            .map(|index| {
+                if exhaustive {
+                    (vector >> index) & 1 == 1
+                } else {
+                    random.next_bool()
+                }
+            })
+            .collect();
+
+        for (test_pins, simulator) in [
+            (&mut test_pins_a, &mut simulator_a),
+            (&mut test_pins_b, &mut simulator_b),
+        ] {
+            for (test_pin, value) in test_pins.iter_mut().zip(values.iter()) {
+                test_pin.set_drive(DriveValue::Strong(*value));
+            }
+            if let Err(error) = simulator.settle_with_limit(options.settle_limit) {
+                panic!("Device didn't settle, {}.", error)
+            }
+        }
+
+        for name in output_names.iter() {
+            let value_a = pins_a[name].borrow().read();
+            let value_b = pins_b[name].borrow().read();
+            if value_a != value_b {
+                return Err(Divergence {
+                    inputs: input_names.iter().cloned().zip(values).collect(),
+                    pin: name.clone(),
+                    value_a,
+                    value_b,
+                });
+            }
+        }
+    }
+    Ok(vectors)
+}
+
+/// Gets the names of the input `Pin`s of the root device, in the order of `inputs` and then by
+/// index within a group, and the names of all of the other `Pin`s, sorted by name.
+fn split_pins(device: &dyn AnyDevice, inputs: &[&str]) -> (Vec<String>, Vec<String>) {
+    let mut outputs: Vec<String> = root_pins(device).into_keys().collect();
+    outputs.sort();
+    let input_names = take_pins(&mut outputs, inputs);
+    (input_names, outputs)
+}
+
+/// Removes the `Pin`s named by `groups` from `names` and returns them, in the order of `groups`
+/// and then by index within a group. A group is either the name of a single `Pin`, such as
+/// `carry_in` or `input[0]`, or of several, such as `input_a`.
+///
+/// Panics if a group doesn't name any of the `Pin`s.
+pub(super) fn take_pins(names: &mut Vec<String>, groups: &[&str]) -> Vec<String> {
+    let mut taken = vec![];
+    for group in groups.iter() {
+        let before = taken.len();
+        if let Some(index) = names.iter().position(|name| name == group) {
+            taken.push(names.remove(index));
+        }
+        for bit in 0.. {
+            let pin_name = format!("{}[{}]", group, bit);
+            match names.iter().position(|name| *name == pin_name) {
+                Some(index) => taken.push(names.remove(index)),
+                None => break,
+            }
+        }
+        if taken.len() == before {
+            panic!("Device has no pin {}.", group)
+        }
+    }
+    taken
+}
+
+/// Gets every `Pin` of the root device by name, e.g. `sum[3]`.
+pub(super) fn root_pins(device: &dyn AnyDevice) -> HashMap<String, Rc<RefCell<Pin>>> {
+    Netlist::new(device).get_devices()[0]
+        .get_pins()
+        .iter()
+        .map(|pin| (pin.get_name().to_string(), pin.get_pin().clone()))
+        .collect()
+}
+
+/// A small xorshift random number generator, since the stimulus only needs to be varied and
+/// repeatable.
+struct Random(u64);
+
+impl Random {
+    /// Gets a random `bool`.
+    fn next_bool(&mut self) -> bool {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 >> 63 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TestNot;
+    use crate::{Behavioral, Device, DeviceContainer};
+    use device_derive::Device;
+
+    // A behavioral model of a bank of gates, which either inverts or copies each input, and drives
+    // `undriven` onto the output if the input isn't driven.
+    #[derive(Device)]
+    #[behavioral]
+    struct Model {
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pins]
+        output: Vec<Rc<RefCell<Pin>>>,
+
+        invert: bool,
+
+        undriven: DriveValue,
+    }
+
+    impl Model {
+        fn new(width: usize, invert: bool) -> Self {
+            Self {
+                input: (0..width)
+                    .map(|_| Pin::new(DriveValue::HighImpedance))
+                    .collect(),
+                output: (0..width)
+                    .map(|_| Pin::new(DriveValue::HighImpedance))
+                    .collect(),
+                invert,
+                undriven: DriveValue::Error,
+            }
+        }
+    }
+
+    impl Behavioral for Model {
+        fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            self.input.clone()
+        }
+
+        fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            self.output.clone()
+        }
+
+        fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue> {
+            inputs
+                .iter()
+                .map(|input| match input {
+                    LogicValue::Driven(value) => DriveValue::Strong(*value != self.invert),
+                    _ => self.undriven,
+                })
+                .collect()
+        }
+    }
+
+    // A bank of NOT gates built from transistors.
+    #[derive(Device)]
+    struct NotGates {
+        #[children]
+        not_gates: Vec<TestNot>,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pins]
+        output: Vec<Rc<RefCell<Pin>>>,
+    }
+
+    impl NotGates {
+        fn new(width: usize) -> Self {
+            let not_gates: Vec<TestNot> = (0..width).map(|_| TestNot::new()).collect();
+            let input = not_gates.iter().map(|g| g.get_input().clone()).collect();
+            let output = not_gates.iter().map(|g| g.get_output().clone()).collect();
+            Self {
+                not_gates,
+                input,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let options = EquivalenceOptions::default();
+        assert_eq!(
+            check_equivalence(Model::new(3, true), NotGates::new(3), &["input"], &options),
+            Ok(8)
+        );
+
+        // Too many inputs to check them all.
+        let options = EquivalenceOptions {
+            exhaustive_inputs: 2,
+            random_vectors: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_equivalence(Model::new(3, true), NotGates::new(3), &["input"], &options),
+            Ok(20)
+        );
+
+        // Far too many inputs to check them all, however many are allowed.
+        let options = EquivalenceOptions {
+            exhaustive_inputs: usize::MAX,
+            random_vectors: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_equivalence(
+                Model::new(64, true),
+                Model::new(64, true),
+                &["input"],
+                &options
+            ),
+            Ok(20)
+        );
+    }
+
+    #[test]
+    fn test_divergence() {
+        let options = EquivalenceOptions::default();
+        let divergence =
+            check_equivalence(Model::new(2, false), NotGates::new(2), &["input"], &options)
+                .unwrap_err();
+        assert_eq!(
+            divergence.get_inputs(),
+            [
+                ("input[0]".to_string(), false),
+                ("input[1]".to_string(), false)
+            ]
+        );
+        assert_eq!(divergence.get_pin(), "output[0]");
+        assert_eq!(divergence.get_value_a(), LogicValue::Driven(false));
+        assert_eq!(divergence.get_value_b(), LogicValue::Driven(true));
+        assert_eq!(
+            divergence.to_string(),
+            "output[0] is Driven(true) but should be Driven(false) when input[0]=0, input[1]=0"
+        );
+    }
+
+    // A transistor whose gate is never driven puts an error on its output, which matches an error
+    // but not high impedance.
+    #[test]
+    fn test_undriven_inputs() {
+        let options = EquivalenceOptions::default();
+        assert_eq!(
+            check_equivalence(
+                Model::new(2, true),
+                NotGates::new(2),
+                &["input[0]"],
+                &options
+            ),
+            Ok(2)
+        );
+
+        let mut model = Model::new(2, true);
+        model.undriven = DriveValue::HighImpedance;
+        let divergence =
+            check_equivalence(model, NotGates::new(2), &["input[0]"], &options).unwrap_err();
+        assert_eq!(divergence.get_inputs(), [("input[0]".to_string(), false)]);
+        assert_eq!(divergence.get_pin(), "output[1]");
+        assert_eq!(divergence.get_value_a(), LogicValue::HighImpedance);
+        assert_eq!(divergence.get_value_b(), LogicValue::Error);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unknown_input() {
+        let options = EquivalenceOptions::default();
+        let _ = check_equivalence(NotGates::new(1), NotGates::new(1), &["carry"], &options);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_different_outputs() {
+        let options = EquivalenceOptions::default();
+        let _ = check_equivalence(NotGates::new(1), NotGates::new(2), &["input"], &options);
+    }
+}
@@ -0,0 +1,13 @@
+mod equivalence;
+mod resource_report;
+mod test_bus;
+mod timing_analysis;
+mod truth_table;
+
+pub use equivalence::{check_equivalence, Divergence, EquivalenceOptions};
+pub use resource_report::{ResourceCount, ResourceReport, ResourceSubtotal};
+pub use test_bus::{read_bus, TestBus};
+pub use timing_analysis::{TimingAnalysis, TimingPath};
+pub use truth_table::{
+    check_integer_truth_table, check_truth_table, TruthTableMismatch, TruthTableValues,
+};
@@ -0,0 +1,345 @@
+use crate::{AnyDevice, Clock, Constant, DriveValue, Netlist, TestPin, Transistor};
+use std::any::Any;
+use std::collections::{BTreeMap, HashSet};
+
+/// The number of each kind of primitive, and of `Wire`s, in part of a `Device` hierarchy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceCount {
+    /// The number of n-channel `Transistor`s.
+    nmos: usize,
+
+    /// The number of p-channel `Transistor`s.
+    pmos: usize,
+
+    /// The number of `Constant`s with a strong drive.
+    strong_constants: usize,
+
+    /// The number of `Constant`s with a weak drive.
+    weak_constants: usize,
+
+    /// The number of `TestPin`s.
+    test_pins: usize,
+
+    /// The number of `Clock`s.
+    clocks: usize,
+
+    /// The number of `Behavioral` devices, which contribute nothing else to the count.
+    behaviorals: usize,
+
+    /// The number of distinct `Wire`s any of the `Pin`s are connected to.
+    wires: usize,
+}
+
+impl ResourceCount {
+    /// Gets the number of n-channel `Transistor`s.
+    pub fn get_nmos(&self) -> usize {
+        self.nmos
+    }
+
+    /// Gets the number of p-channel `Transistor`s.
+    pub fn get_pmos(&self) -> usize {
+        self.pmos
+    }
+
+    /// Gets the total number of `Transistor`s.
+    pub fn get_transistors(&self) -> usize {
+        self.nmos + self.pmos
+    }
+
+    /// Gets the number of `Constant`s with a strong drive.
+    pub fn get_strong_constants(&self) -> usize {
+        self.strong_constants
+    }
+
+    /// Gets the number of `Constant`s with a weak drive.
+    pub fn get_weak_constants(&self) -> usize {
+        self.weak_constants
+    }
+
+    /// Gets the number of `TestPin`s.
+    pub fn get_test_pins(&self) -> usize {
+        self.test_pins
+    }
+
+    /// Gets the number of `Clock`s.
+    pub fn get_clocks(&self) -> usize {
+        self.clocks
+    }
+
+    /// Gets the number of `Behavioral` devices.
+    pub fn get_behaviorals(&self) -> usize {
+        self.behaviorals
+    }
+
+    /// Gets the number of distinct `Wire`s any of the `Pin`s are connected to.
+    pub fn get_wires(&self) -> usize {
+        self.wires
+    }
+
+    /// Adds another count to this one. `Wire`s are simply added, so any `Wire`s shared between
+    /// the two are counted twice.
+    fn add(&mut self, other: &ResourceCount) {
+        self.nmos += other.nmos;
+        self.pmos += other.pmos;
+        self.strong_constants += other.strong_constants;
+        self.weak_constants += other.weak_constants;
+        self.test_pins += other.test_pins;
+        self.clocks += other.clocks;
+        self.behaviorals += other.behaviorals;
+        self.wires += other.wires;
+    }
+}
+
+/// The subtotal for all of the children of a device that have the same `type_name`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceSubtotal {
+    /// The number of children with the `type_name`.
+    instances: usize,
+
+    /// The sum of the counts of those children. `Wire`s shared between the children are counted
+    /// once for each child.
+    count: ResourceCount,
+}
+
+impl ResourceSubtotal {
+    /// Gets the number of children with the `type_name`.
+    pub fn get_instances(&self) -> usize {
+        self.instances
+    }
+
+    /// Gets the sum of the counts of those children.
+    pub fn get_count(&self) -> &ResourceCount {
+        &self.count
+    }
+}
+
+/// A breakdown of the resources used by a `Device` hierarchy, e.g. to compare the number of
+/// `Transistor`s used by different implementations of the same logic.
+///
+/// There is one `ResourceReport` for every device in the hierarchy, each containing the totals for
+/// that device and everything within it, as well as subtotals for its children grouped by
+/// `type_name`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceReport {
+    /// The name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    name: String,
+
+    /// The `type_name` of the device.
+    type_name: String,
+
+    /// The totals for the device and everything within it.
+    count: ResourceCount,
+
+    /// The subtotals for the device's children, by `type_name`.
+    subtotals: BTreeMap<String, ResourceSubtotal>,
+
+    /// The reports for the device's children, sorted by name.
+    children: Vec<ResourceReport>,
+}
+
+impl ResourceReport {
+    /// Creates a new `ResourceReport` for the given `Device` and everything within it.
+    pub fn new(device: &dyn AnyDevice) -> Self {
+        let netlist = Netlist::new(device);
+        Self::new_impl(&netlist, 0).0
+    }
+
+    /// Recursively creates the report for a device in the `Netlist`. Also returns the IDs of every
+    /// `Wire` in the device, so they're only counted once.
+    fn new_impl(netlist: &Netlist, device: usize) -> (Self, HashSet<usize>) {
+        let netlist_device = &netlist.get_devices()[device];
+        let mut wires: HashSet<usize> = netlist_device
+            .get_pins()
+            .iter()
+            .map(|pin| pin.get_wire())
+            .collect();
+        let mut count = ResourceCount::default();
+
+        let any = netlist_device.get_device() as &dyn Any;
+        if let Some(transistor) = any.downcast_ref::<Transistor>() {
+            match transistor.get_activation() {
+                true => count.nmos += 1,
+                false => count.pmos += 1,
+            }
+        } else if let Some(constant) = any.downcast_ref::<Constant>() {
+            match constant.get_output().borrow().get_drive() {
+                DriveValue::Strong(_) => count.strong_constants += 1,
+                DriveValue::Weak(_) => count.weak_constants += 1,
+                _ => (),
+            }
+        } else if any.is::<TestPin>() {
+            count.test_pins += 1;
+        } else if any.is::<Clock>() {
+            count.clocks += 1;
+        } else if netlist_device.get_device().behavioral().is_some() {
+            count.behaviorals += 1;
+        }
+
+        let mut subtotals: BTreeMap<String, ResourceSubtotal> = BTreeMap::new();
+        let mut children = vec![];
+        for child in netlist_device.get_children().iter() {
+            let (report, child_wires) = Self::new_impl(netlist, *child);
+            count.add(&ResourceCount {
+                wires: 0,
+                ..report.count
+            });
+            let subtotal = subtotals.entry(report.type_name.clone()).or_default();
+            subtotal.instances += 1;
+            subtotal.count.add(&report.count);
+            wires.extend(child_wires);
+            children.push(report);
+        }
+        count.wires = wires.len();
+
+        let report = Self {
+            name: netlist_device.get_name().to_string(),
+            type_name: netlist_device.get_device().type_name(),
+            count,
+            subtotals,
+            children,
+        };
+        (report, wires)
+    }
+
+    /// Gets the name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the `type_name` of the device.
+    pub fn get_type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Gets the totals for the device and everything within it.
+    pub fn get_count(&self) -> &ResourceCount {
+        &self.count
+    }
+
+    /// Gets the subtotals for the device's children, by `type_name`.
+    pub fn get_subtotals(&self) -> &BTreeMap<String, ResourceSubtotal> {
+        &self.subtotals
+    }
+
+    /// Gets the reports for the device's children, sorted by name.
+    pub fn get_children(&self) -> &[ResourceReport] {
+        &self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{BehavioralNot, TestNot};
+    use crate::{Device, DeviceContainer, Pin};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Two NOT gates in series, driven by a TestPin, alongside a Clock.
+    #[derive(Device)]
+    struct TestBuffer {
+        #[children]
+        not_gates: Vec<TestNot>,
+
+        #[child]
+        test_pin: TestPin,
+
+        #[child]
+        clock: Clock,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    #[test]
+    fn test_resource_report() {
+        let not_gates = vec![
+            TestNot::with_weak_pull_down(),
+            TestNot::with_weak_pull_down(),
+        ];
+        let test_pin = TestPin::new(DriveValue::Strong(true));
+        let output = not_gates[1].get_output().clone();
+        Pin::connect(test_pin.get_output(), not_gates[0].get_input());
+        Pin::connect(not_gates[0].get_output(), not_gates[1].get_input());
+        let buffer = TestBuffer {
+            not_gates,
+            test_pin,
+            clock: Clock::new(1, 1),
+            output,
+        };
+
+        let report = ResourceReport::new(&buffer);
+        let not_count = ResourceCount {
+            nmos: 1,
+            pmos: 1,
+            strong_constants: 2,
+            weak_constants: 1,
+            test_pins: 0,
+            clocks: 0,
+            behaviorals: 0,
+            wires: 4,
+        };
+        assert_eq!(report.get_name(), "");
+        assert_eq!(report.get_type_name(), "TestBuffer");
+        assert_eq!(
+            *report.get_count(),
+            ResourceCount {
+                nmos: 2,
+                pmos: 2,
+                strong_constants: 4,
+                weak_constants: 2,
+                test_pins: 1,
+                clocks: 1,
+                behaviorals: 0,
+                wires: 8,
+            }
+        );
+        assert_eq!(report.get_count().get_transistors(), 4);
+        assert_eq!(
+            report.get_subtotals()["TestNot"],
+            ResourceSubtotal {
+                instances: 2,
+                count: ResourceCount {
+                    nmos: 2,
+                    pmos: 2,
+                    strong_constants: 4,
+                    weak_constants: 2,
+                    test_pins: 0,
+                    clocks: 0,
+                    behaviorals: 0,
+                    wires: 8,
+                },
+            }
+        );
+        assert_eq!(report.get_subtotals()["TestPin"].get_instances(), 1);
+        assert_eq!(report.get_subtotals()["Clock"].get_count().get_clocks(), 1);
+        assert_eq!(report.get_children().len(), 4);
+        assert_eq!(report.get_children()[0].get_name(), "clock");
+        assert_eq!(report.get_children()[1].get_name(), "not_gates[0]");
+        assert_eq!(*report.get_children()[1].get_count(), not_count);
+        assert_eq!(
+            report.get_children()[1].get_subtotals()["Transistor"].get_instances(),
+            2
+        );
+        assert_eq!(
+            report.get_children()[1].get_subtotals()["Constant"].get_instances(),
+            3
+        );
+        assert_eq!(report.get_children()[3].get_name(), "test_pin");
+    }
+
+    #[test]
+    fn test_resource_report_behavioral() {
+        let device = BehavioralNot::new();
+        let report = ResourceReport::new(&device);
+        assert_eq!(
+            *report.get_count(),
+            ResourceCount {
+                behaviorals: 1,
+                wires: 2,
+                ..ResourceCount::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{DriveValue, LogicValue, Pin, TestPin};
+use std::cell::RefCell;
+use std::iter::zip;
+use std::rc::Rc;
+
+/// A group of `TestPin`s connected to a group of `Pin`s, e.g. the `input_a` of an adder, so the
+/// whole group can be driven with an integer in tests.
+///
+/// Like a single `TestPin`, the `TestBus` must outlive any use of the `Device` it's connected to.
+pub struct TestBus {
+    /// One `TestPin` for each `Pin`, in the same order.
+    test_pins: Vec<TestPin>,
+}
+
+impl TestBus {
+    /// Creates a `TestPin` with the initial drive for each of the `Pin`s, and connects them.
+    ///
+    /// Panics if there are more `Pin`s than bits in a `usize`, since they couldn't all be set.
+    pub fn new(pins: &[Rc<RefCell<Pin>>], initial_drive: DriveValue) -> Self {
+        check_width(pins);
+        let test_pins: Vec<TestPin> = pins.iter().map(|_| TestPin::new(initial_drive)).collect();
+        for (test_pin, pin) in zip(test_pins.iter(), pins.iter()) {
+            Pin::connect(test_pin.get_output(), pin);
+        }
+        Self { test_pins }
+    }
+
+    /// Strongly drives an integer onto the `Pin`s, with bit `i` of the value driving the `i`th
+    /// `Pin`.
+    pub fn set_value(&mut self, value: usize) {
+        for (index, test_pin) in self.test_pins.iter_mut().enumerate() {
+            test_pin.set_drive(DriveValue::Strong((value >> index) & 1 == 1));
+        }
+    }
+
+    /// Sets the drive of every `TestPin`, e.g. to `DriveValue::HighImpedance` to release a shared
+    /// bus.
+    pub fn set_drive(&mut self, drive: DriveValue) {
+        for test_pin in self.test_pins.iter_mut() {
+            test_pin.set_drive(drive);
+        }
+    }
+
+    /// Gets the `TestPin`s, in the same order as the `Pin`s they're connected to.
+    pub fn get_test_pins(&self) -> &[TestPin] {
+        &self.test_pins
+    }
+}
+
+/// Reads a group of `Pin`s as an integer, with the `i`th `Pin` being bit `i` of the value.
+///
+/// Returns `None` if any of the `Pin`s doesn't have a driven value. Panics if there are more `Pin`s
+/// than bits in a `usize`.
+pub fn read_bus(pins: &[Rc<RefCell<Pin>>]) -> Option<usize> {
+    check_width(pins);
+    pins.iter()
+        .enumerate()
+        .try_fold(0, |value, (index, pin)| match pin.borrow().read() {
+            LogicValue::Driven(bit) => Some(value | (bit as usize) << index),
+            _ => None,
+        })
+}
+
+/// Panics if a group of `Pin`s is too wide to be represented as a `usize`.
+fn check_width(pins: &[Rc<RefCell<Pin>>]) {
+    if pins.len() > usize::BITS as usize {
+        panic!("Bus width must be at most {} bits.", usize::BITS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus() {
+        let pins = new_pins(3);
+        let mut test_bus = TestBus::new(&pins, DriveValue::HighImpedance);
+        assert_eq!(test_bus.get_test_pins().len(), 3);
+        assert_eq!(read_bus(&pins), None);
+
+        for value in 0..8 {
+            test_bus.set_value(value);
+            assert_eq!(read_bus(&pins), Some(value));
+        }
+
+        test_bus.set_drive(DriveValue::Weak(true));
+        assert_eq!(read_bus(&pins), Some(7));
+        test_bus.set_drive(DriveValue::Error);
+        assert_eq!(read_bus(&pins), None);
+    }
+
+    fn new_pins(count: usize) -> Vec<Rc<RefCell<Pin>>> {
+        (0..count)
+            .map(|_| Pin::new(DriveValue::HighImpedance))
+            .collect()
+    }
+
+    #[test]
+    fn test_widest_bus() {
+        let pins = new_pins(usize::BITS as usize);
+        let mut test_bus = TestBus::new(&pins, DriveValue::HighImpedance);
+        test_bus.set_value(usize::MAX);
+        assert_eq!(read_bus(&pins), Some(usize::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_test_bus_width() {
+        TestBus::new(
+            &new_pins(usize::BITS as usize + 1),
+            DriveValue::HighImpedance,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_read_bus_width() {
+        read_bus(&new_pins(usize::BITS as usize + 1));
+    }
+}
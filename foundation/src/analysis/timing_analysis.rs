@@ -0,0 +1,346 @@
+use crate::{AnyDevice, Netlist, Terminal};
+
+/// The slowest path from one input `Pin` to one output `Pin` found by a `TimingAnalysis`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimingPath {
+    /// The name of the input `Pin` on the root device, e.g. `input_a[0]`.
+    input: String,
+
+    /// The name of the output `Pin` on the root device, e.g. `sum[3]`.
+    output: String,
+
+    /// The worst-case number of `Transistor` stages between the input and the output, i.e. the
+    /// number of ticks it takes a change to propagate.
+    stages: usize,
+
+    /// The hierarchical names of the `Transistor`s along the path, in order from the input to the
+    /// output, e.g. `adders[0].xor_gate.nmos[1]`.
+    transistors: Vec<String>,
+}
+
+impl TimingPath {
+    /// Gets the name of the input `Pin` on the root device, e.g. `input_a[0]`.
+    pub fn get_input(&self) -> &str {
+        &self.input
+    }
+
+    /// Gets the name of the output `Pin` on the root device, e.g. `sum[3]`.
+    pub fn get_output(&self) -> &str {
+        &self.output
+    }
+
+    /// Gets the worst-case number of `Transistor` stages between the input and the output.
+    pub fn get_stages(&self) -> usize {
+        self.stages
+    }
+
+    /// Gets the hierarchical names of the `Transistor`s along the path, in order from the input to
+    /// the output.
+    pub fn get_transistors(&self) -> &[String] {
+        &self.transistors
+    }
+}
+
+/// A static timing analysis of a `Device`, which finds the worst-case number of `Transistor`
+/// stages from every input `Pin` to every output `Pin` of the root device, regardless of the
+/// values applied to the inputs.
+///
+/// The flattened `Transistor`s form a graph between `Wire`s, where each `Transistor` is an edge
+/// from its gate `Wire` and from its source `Wire` to its drain `Wire`, since a change to either
+/// reaches the drain one tick later. Any `Pin` on a `Wire` which is read by a `Transistor` is an
+/// input, and any `Pin` on a `Wire` driven by a `Transistor` is an output, so a `Pin` can be both.
+///
+/// Feedback loops, such as in latches, would make the longest path infinite. They're broken by
+/// ignoring the edge which closes each loop, as found by a depth first search starting from the
+/// inputs, so paths through sequential logic are only approximate.
+///
+/// `Behavioral` devices have no `Transistor`s, so no paths are found through them, and a `Pin`
+/// connected only to a `Behavioral` device is neither an input nor an output.
+pub struct TimingAnalysis {
+    /// The slowest path for every pair of input and output `Pin`s where the output can be reached
+    /// from the input, ordered by input and then by output, in the order of the root device's
+    /// `Pin`s.
+    paths: Vec<TimingPath>,
+}
+
+impl TimingAnalysis {
+    /// Creates a new `TimingAnalysis` of the given `Device`.
+    pub fn new(device: &dyn AnyDevice) -> Self {
+        let netlist = Netlist::new(device);
+        let wires = netlist.get_wires();
+
+        // Every transistor is an edge from its gate and its source to its drain.
+        let mut edges: Vec<Vec<(usize, usize)>> = vec![vec![]; wires.len()];
+        for (index, transistor) in netlist.get_transistors().iter().enumerate() {
+            edges[transistor.get_gate()].push((transistor.get_drain(), index));
+            if transistor.get_source() != transistor.get_gate() {
+                edges[transistor.get_source()].push((transistor.get_drain(), index));
+            }
+        }
+
+        let root_pins = netlist.get_devices()[0].get_pins();
+        let inputs: Vec<(&str, usize)> = root_pins
+            .iter()
+            .filter(|pin| {
+                wires[pin.get_wire()]
+                    .get_terminals()
+                    .iter()
+                    .any(|terminal| matches!(terminal, Terminal::Gate(_) | Terminal::Source(_)))
+            })
+            .map(|pin| (pin.get_name(), pin.get_wire()))
+            .collect();
+        let outputs: Vec<(&str, usize)> = root_pins
+            .iter()
+            .filter(|pin| {
+                wires[pin.get_wire()]
+                    .get_terminals()
+                    .iter()
+                    .any(|terminal| matches!(terminal, Terminal::Drain(_)))
+            })
+            .map(|pin| (pin.get_name(), pin.get_wire()))
+            .collect();
+
+        let order = topological_order(&mut edges, inputs.iter().map(|(_, wire)| *wire));
+
+        let mut paths = vec![];
+        for (input, input_wire) in inputs.iter() {
+            // The longest path to every wire, as the number of stages, and the previous wire and
+            // transistor on the path.
+            let mut longest: Vec<Option<usize>> = vec![None; wires.len()];
+            let mut previous: Vec<Option<(usize, usize)>> = vec![None; wires.len()];
+            longest[*input_wire] = Some(0);
+            for wire in order.iter() {
+                let Some(stages) = longest[*wire] else {
+                    continue;
+                };
+                for (drain, transistor) in edges[*wire].iter() {
+                    if longest[*drain].is_none_or(|existing| stages + 1 > existing) {
+                        longest[*drain] = Some(stages + 1);
+                        previous[*drain] = Some((*wire, *transistor));
+                    }
+                }
+            }
+
+            for (output, output_wire) in outputs.iter() {
+                let Some(stages) = longest[*output_wire] else {
+                    continue;
+                };
+                let mut transistors = vec![];
+                let mut current = *output_wire;
+                while let Some((wire, transistor)) = previous[current] {
+                    let device = netlist.get_transistors()[transistor].get_device();
+                    transistors.push(netlist.get_path(device));
+                    current = wire;
+                }
+                transistors.reverse();
+                paths.push(TimingPath {
+                    input: input.to_string(),
+                    output: output.to_string(),
+                    stages,
+                    transistors,
+                });
+            }
+        }
+
+        Self { paths }
+    }
+
+    /// Gets the slowest path for every pair of input and output `Pin`s where the output can be
+    /// reached from the input, ordered by input and then by output. Both follow the order of the
+    /// root device's `Pin`s, which are sorted by name, with each group in index order, so e.g.
+    /// `input[2]` comes before `input[10]`.
+    pub fn get_paths(&self) -> &[TimingPath] {
+        &self.paths
+    }
+
+    /// Gets the slowest path from the given input `Pin` to the given output `Pin`, or `None` if the
+    /// output can't be reached from the input.
+    pub fn get_path(&self, input: &str, output: &str) -> Option<&TimingPath> {
+        self.paths
+            .iter()
+            .find(|path| path.input == input && path.output == output)
+    }
+
+    /// Gets the slowest path through the whole `Device`, or `None` if no output can be reached
+    /// from any input. If several paths are equally slow, the first one is returned.
+    pub fn get_critical_path(&self) -> Option<&TimingPath> {
+        self.paths.iter().rev().max_by_key(|path| path.stages)
+    }
+}
+
+/// Orders the `Wire`s so every edge goes from an earlier `Wire` to a later one, using a depth first
+/// search starting from the given `Wire`s, then all of the others. Edges which would close a loop
+/// are removed.
+fn topological_order(
+    edges: &mut [Vec<(usize, usize)>],
+    starts: impl Iterator<Item = usize>,
+) -> Vec<usize> {
+    // Whether each wire hasn't been visited yet, is being visited, or has been finished.
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Active,
+        Finished,
+    }
+
+    let mut state = vec![State::New; edges.len()];
+    let mut finished = vec![];
+    for start in starts.chain(0..edges.len()) {
+        if state[start] != State::New {
+            continue;
+        }
+
+        // The stack holds each wire being visited, and the index of the next edge to follow.
+        state[start] = State::Active;
+        let mut stack = vec![(start, 0)];
+        while let Some((wire, next)) = stack.last_mut() {
+            let wire = *wire;
+            if *next == edges[wire].len() {
+                state[wire] = State::Finished;
+                finished.push(wire);
+                stack.pop();
+                continue;
+            }
+
+            let (drain, _) = edges[wire][*next];
+            match state[drain] {
+                State::New => {
+                    *next += 1;
+                    state[drain] = State::Active;
+                    stack.push((drain, 0));
+                }
+                State::Active => {
+                    edges[wire].remove(*next);
+                }
+                State::Finished => *next += 1,
+            }
+        }
+    }
+
+    finished.reverse();
+    finished
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TestNot;
+    use crate::{Device, DeviceContainer, Pin};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // A NOT gate, which drives two more NOT gates connected in a loop, like a latch.
+    #[derive(Device)]
+    struct TestLatch {
+        #[children]
+        not_gates: Vec<TestNot>,
+
+        #[pin]
+        input: Rc<RefCell<Pin>>,
+
+        #[pin]
+        middle: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    #[test]
+    fn test_timing_analysis() {
+        let not_gates = vec![TestNot::new(), TestNot::new(), TestNot::new()];
+        let input = not_gates[0].get_input().clone();
+        let middle = not_gates[0].get_output().clone();
+        let output = not_gates[1].get_output().clone();
+        Pin::connect(not_gates[0].get_output(), not_gates[1].get_input());
+        Pin::connect(not_gates[1].get_output(), not_gates[2].get_input());
+        Pin::connect(not_gates[2].get_output(), not_gates[1].get_input());
+        let latch = TestLatch {
+            not_gates,
+            input,
+            middle,
+            output,
+        };
+
+        let analysis = TimingAnalysis::new(&latch);
+        let critical_path = TimingPath {
+            input: "input".to_string(),
+            output: "output".to_string(),
+            stages: 2,
+            transistors: vec![
+                "not_gates[0].nmos".to_string(),
+                "not_gates[1].nmos".to_string(),
+            ],
+        };
+        assert_eq!(analysis.get_critical_path(), Some(&critical_path));
+        assert_eq!(analysis.get_path("input", "output"), Some(&critical_path));
+        assert_eq!(
+            analysis.get_path("input", "middle").unwrap().get_stages(),
+            1
+        );
+        assert_eq!(
+            analysis.get_path("middle", "output").unwrap().get_stages(),
+            1
+        );
+        assert_eq!(
+            analysis.get_path("middle", "middle").unwrap().get_stages(),
+            0
+        );
+
+        // The loop is broken by removing the edge from the output back to the middle.
+        assert_eq!(analysis.get_path("output", "middle"), None);
+        assert_eq!(analysis.get_path("output", "input"), None);
+
+        let names: Vec<(&str, &str)> = analysis
+            .get_paths()
+            .iter()
+            .map(|path| (path.get_input(), path.get_output()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("input", "middle"),
+                ("input", "output"),
+                ("middle", "middle"),
+                ("middle", "output"),
+                ("output", "output"),
+            ]
+        );
+    }
+
+    // A bank of NOT gates, with more than ten so the order of the indices can be seen.
+    #[derive(Device)]
+    struct TestNotGates {
+        #[children]
+        not_gates: Vec<TestNot>,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pins]
+        output: Vec<Rc<RefCell<Pin>>>,
+    }
+
+    #[test]
+    fn test_timing_analysis_order() {
+        let not_gates: Vec<TestNot> = (0..11).map(|_| TestNot::new()).collect();
+        let input = not_gates.iter().map(|g| g.get_input().clone()).collect();
+        let output = not_gates.iter().map(|g| g.get_output().clone()).collect();
+        let device = TestNotGates {
+            not_gates,
+            input,
+            output,
+        };
+
+        let analysis = TimingAnalysis::new(&device);
+        let names: Vec<(String, String)> = analysis
+            .get_paths()
+            .iter()
+            .map(|path| (path.get_input().to_string(), path.get_output().to_string()))
+            .collect();
+        let expected: Vec<(String, String)> = (0..11)
+            .map(|index| (format!("input[{}]", index), format!("output[{}]", index)))
+            .collect();
+        assert_eq!(names, expected);
+    }
+}
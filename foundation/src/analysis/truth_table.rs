@@ -0,0 +1,384 @@
+use super::equivalence::{root_pins, take_pins};
+use crate::{AnyDevice, DriveValue, LogicValue, Pin, Simulator, TestPin, DRIVE_VALUES};
+use std::fmt;
+use std::iter::zip;
+
+/// The number of ticks the `Device` is given to settle after the inputs change.
+const SETTLE_LIMIT: usize = 10_000;
+
+/// Which values [`check_truth_table`] drives onto each input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TruthTableValues {
+    /// Only `DriveValue::Strong` `false` and `true`.
+    Booleans,
+
+    /// Every value in `DRIVE_VALUES`, to check how errors, weak drives, and floating inputs are
+    /// handled too.
+    DriveValues,
+}
+
+/// A row of a truth table where an output of the `Device` didn't have the expected value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruthTableMismatch {
+    /// The name and drive of every input `Pin` in the row.
+    inputs: Vec<(String, DriveValue)>,
+
+    /// The name of the output `Pin`.
+    pin: String,
+
+    /// The value the output `Pin` should have had.
+    expected: LogicValue,
+
+    /// The value the output `Pin` actually had.
+    actual: LogicValue,
+}
+
+impl TruthTableMismatch {
+    /// Gets the name and drive of every input `Pin` in the row, in the order the inputs were given
+    /// to [`check_truth_table`].
+    pub fn get_inputs(&self) -> &[(String, DriveValue)] {
+        &self.inputs
+    }
+
+    /// Gets the name of the output `Pin`, e.g. `sum[3]`.
+    pub fn get_pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// Gets the value the output `Pin` should have had.
+    pub fn get_expected(&self) -> LogicValue {
+        self.expected
+    }
+
+    /// Gets the value the output `Pin` actually had.
+    pub fn get_actual(&self) -> LogicValue {
+        self.actual
+    }
+}
+
+impl fmt::Display for TruthTableMismatch {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|(name, drive)| format!("{}={:?}", name, drive))
+            .collect();
+        write!(
+            formatter,
+            "{} is {:?} but should be {:?} when {}",
+            self.pin,
+            self.actual,
+            self.expected,
+            inputs.join(", ")
+        )
+    }
+}
+
+/// Checks every row of the truth table of a `Device`.
+///
+/// `inputs` and `outputs` are the names of `Pin`s, or groups of `Pin`s such as `input_a`, of the
+/// root device. A `TestPin` is connected to each input, and every combination of `values` is
+/// driven onto them, with the first input changing fastest. After the `Device` settles, `expected`
+/// is called with the drive of each input, and must return the value of each output, in order.
+///
+/// The `Device` is consumed since the `TestPin`s are dropped at the end, after which the `Device`
+/// can't be used.
+///
+/// Returns every output which didn't have the expected value, so an empty `Vec` means the `Device`
+/// matches the truth table.
+///
+/// Panics if one of the `inputs` or `outputs` doesn't name a `Pin`, if `expected` returns the wrong
+/// number of values, or if the `Device` doesn't settle.
+pub fn check_truth_table(
+    device: impl AnyDevice,
+    inputs: &[&str],
+    outputs: &[&str],
+    values: TruthTableValues,
+    expected: impl Fn(&[DriveValue]) -> Vec<LogicValue>,
+) -> Vec<TruthTableMismatch> {
+    let pins = root_pins(&device);
+    let mut names: Vec<String> = pins.keys().cloned().collect();
+    let input_names = take_pins(&mut names, inputs);
+    let output_names = take_pins(&mut names, outputs);
+
+    let mut test_pins: Vec<TestPin> = input_names
+        .iter()
+        .map(|name| {
+            let test_pin = TestPin::new(DriveValue::HighImpedance);
+            Pin::connect(test_pin.get_output(), &pins[name]);
+            test_pin
+        })
+        .collect();
+    let mut simulator = Simulator::new(&device);
+
+    let values = match values {
+        TruthTableValues::Booleans => &[DriveValue::Strong(false), DriveValue::Strong(true)],
+        TruthTableValues::DriveValues => DRIVE_VALUES,
+    };
+    let mut mismatches = vec![];
+    for row in 0..values.len().pow(input_names.len() as u32) {
+        let drives: Vec<DriveValue> = (0..input_names.len())
+            .map(|index| values[row / values.len().pow(index as u32) % values.len()])
+            .collect();
+        for (test_pin, drive) in test_pins.iter_mut().zip(drives.iter()) {
+            test_pin.set_drive(*drive);
+        }
+        if let Err(error) = simulator.settle_with_limit(SETTLE_LIMIT) {
+            panic!("Device didn't settle, {}.", error)
+        }
+
+        let expected_values = expected(&drives);
+        if expected_values.len() != output_names.len() {
+            panic!(
+                "Expected {} output values, got {}.",
+                output_names.len(),
+                expected_values.len()
+            )
+        }
+        for (name, expected) in output_names.iter().zip(expected_values) {
+            let actual = pins[name].borrow().read();
+            if actual != expected {
+                mismatches.push(TruthTableMismatch {
+                    inputs: input_names.iter().cloned().zip(drives.clone()).collect(),
+                    pin: name.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+/// Checks every row of the truth table of a `Device` whose inputs and outputs are unsigned
+/// integers, e.g. an adder.
+///
+/// This is [`check_truth_table`] with `TruthTableValues::Booleans`, except that `expected` is
+/// called with the value of each of the `inputs` as an integer, and must return the value of each
+/// of the `outputs` in the same way. Bit `i` of a value is the `i`th `Pin` of the group, and a
+/// single `Pin` is a group with one bit. Any bits of an expected value beyond the width of its
+/// group are ignored. The first input counts up fastest.
+///
+/// Panics in the same cases as [`check_truth_table`].
+pub fn check_integer_truth_table(
+    device: impl AnyDevice,
+    inputs: &[&str],
+    outputs: &[&str],
+    expected: impl Fn(&[usize]) -> Vec<usize>,
+) -> Vec<TruthTableMismatch> {
+    let mut names: Vec<String> = root_pins(&device).into_keys().collect();
+    let mut widths = |groups: &[&str]| -> Vec<usize> {
+        groups
+            .iter()
+            .map(|group| take_pins(&mut names, &[group]).len())
+            .collect()
+    };
+    let input_widths = widths(inputs);
+    let output_widths = widths(outputs);
+
+    let expected = |drives: &[DriveValue]| -> Vec<LogicValue> {
+        let mut drives = drives.iter();
+        let values: Vec<usize> = input_widths
+            .iter()
+            .map(|width| {
+                drives
+                    .by_ref()
+                    .take(*width)
+                    .enumerate()
+                    .fold(0, |value, (bit, drive)| {
+                        value | ((*drive == DriveValue::Strong(true)) as usize) << bit
+                    })
+            })
+            .collect();
+        let expected_values = expected(&values);
+        if expected_values.len() != output_widths.len() {
+            panic!(
+                "Expected {} output values, got {}.",
+                output_widths.len(),
+                expected_values.len()
+            )
+        }
+        zip(output_widths.iter(), expected_values)
+            .flat_map(|(width, value)| {
+                (0..*width).map(move |bit| LogicValue::Driven((value >> bit) & 1 == 1))
+            })
+            .collect()
+    };
+    check_truth_table(
+        device,
+        inputs,
+        outputs,
+        TruthTableValues::Booleans,
+        expected,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constant, Device, DeviceContainer, Transistor};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // An NMOS transistor which pulls its output low, with a weak pull-up.
+    #[derive(Device)]
+    struct PullDown {
+        #[child]
+        weak_true: Constant,
+
+        #[child]
+        strong_false: Constant,
+
+        #[child]
+        nmos: Transistor,
+
+        #[pin]
+        input: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl PullDown {
+        fn new() -> Self {
+            let weak_true = Constant::new_weak(true);
+            let strong_false = Constant::new_strong(false);
+            let nmos = Transistor::new_nmos();
+            let input = nmos.get_gate().clone();
+            let output = nmos.get_drain().clone();
+            Pin::connect(strong_false.get_output(), nmos.get_source());
+            Pin::connect(weak_true.get_output(), &output);
+            Self {
+                weak_true,
+                strong_false,
+                nmos,
+                input,
+                output,
+            }
+        }
+    }
+
+    fn expected_not(drives: &[DriveValue]) -> Vec<LogicValue> {
+        match LogicValue::from(drives[0]) {
+            LogicValue::Driven(value) => vec![LogicValue::Driven(!value)],
+            _ => vec![LogicValue::Error],
+        }
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let mismatches = check_truth_table(
+            PullDown::new(),
+            &["input"],
+            &["output"],
+            TruthTableValues::Booleans,
+            expected_not,
+        );
+        assert_eq!(mismatches, []);
+
+        let mismatches = check_truth_table(
+            PullDown::new(),
+            &["input"],
+            &["output"],
+            TruthTableValues::DriveValues,
+            expected_not,
+        );
+        assert_eq!(mismatches, []);
+    }
+
+    // Every mismatch is reported, not just the first.
+    #[test]
+    fn test_truth_table_mismatches() {
+        let mismatches = check_truth_table(
+            PullDown::new(),
+            &["input"],
+            &["output"],
+            TruthTableValues::Booleans,
+            |_| vec![LogicValue::HighImpedance],
+        );
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(
+            mismatches[0].get_inputs(),
+            [("input".to_string(), DriveValue::Strong(false))]
+        );
+        assert_eq!(mismatches[0].get_pin(), "output");
+        assert_eq!(mismatches[0].get_expected(), LogicValue::HighImpedance);
+        assert_eq!(mismatches[0].get_actual(), LogicValue::Driven(true));
+        assert_eq!(
+            mismatches[1].to_string(),
+            "output is Driven(false) but should be HighImpedance when input=Strong(true)"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truth_table_unknown_output() {
+        check_truth_table(
+            PullDown::new(),
+            &["input"],
+            &["sum"],
+            TruthTableValues::Booleans,
+            expected_not,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truth_table_wrong_expected_length() {
+        check_truth_table(
+            PullDown::new(),
+            &["input"],
+            &["output"],
+            TruthTableValues::Booleans,
+            |_| vec![],
+        );
+    }
+
+    // A pair of `PullDown`s, so there's a group of inputs and a group of outputs.
+    #[derive(Device)]
+    struct PullDowns {
+        #[children]
+        pull_downs: Vec<PullDown>,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pins]
+        output: Vec<Rc<RefCell<Pin>>>,
+    }
+
+    impl PullDowns {
+        fn new() -> Self {
+            let pull_downs = vec![PullDown::new(), PullDown::new()];
+            let input = pull_downs.iter().map(|p| p.get_input().clone()).collect();
+            let output = pull_downs.iter().map(|p| p.get_output().clone()).collect();
+            Self {
+                pull_downs,
+                input,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_truth_table() {
+        let mismatches =
+            check_integer_truth_table(PullDowns::new(), &["input"], &["output"], |values| {
+                vec![!values[0]]
+            });
+        assert_eq!(mismatches, []);
+
+        // Only the wrong bit of the wrong row is reported.
+        let mismatches =
+            check_integer_truth_table(PullDowns::new(), &["input"], &["output"], |values| {
+                vec![if values[0] == 2 { 3 } else { !values[0] }]
+            });
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "output[1] is Driven(false) but should be Driven(true) when input[0]=Strong(false), \
+             input[1]=Strong(true)"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Pin;
+use crate::{Behavioral, Pin};
 use std::any::Any;
 use std::cell::{Ref, RefMut};
 use std::collections::HashMap;
@@ -20,16 +20,23 @@ pub trait Device {
     fn type_name(&self) -> String;
 
     /// Gets all of the `Pin`s this `Device` owns.
-    fn pins(&self) -> HashMap<String, DeviceContainer<Ref<Pin>>>;
+    fn pins(&self) -> HashMap<String, DeviceContainer<Ref<'_, Pin>>>;
 
     /// Gets all of the `Pin`s this `Device` owns, mutably.
-    fn pins_mut(&mut self) -> HashMap<String, DeviceContainer<RefMut<Pin>>>;
+    fn pins_mut(&mut self) -> HashMap<String, DeviceContainer<RefMut<'_, Pin>>>;
 
     /// Gets all of the child `Device`s this `Device` owns.
     fn children(&self) -> HashMap<String, DeviceContainer<&dyn AnyDevice>>;
 
     /// Gets all of the child `Device`s this `Device` owns, mutably.
     fn children_mut(&mut self) -> HashMap<String, DeviceContainer<&mut dyn AnyDevice>>;
+
+    /// Gets this `Device` as a `Behavioral`, if its outputs are computed by Rust code, so the
+    /// `Simulator` can evaluate it. Returns `None` by default, the `device_derive::Device` macro
+    /// returns the `Device` itself if the `struct` is marked `#[behavioral]`.
+    fn behavioral(&self) -> Option<&dyn Behavioral> {
+        None
+    }
 }
 
 /// A composite trait for a `Device` which is also `Any`. This allows the concrete type to be
@@ -0,0 +1,266 @@
+use crate::{AnyDevice, Netlist};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Writes a `Device` hierarchy as a Graphviz DOT graph, with every device fully expanded.
+///
+/// Every device with children becomes a cluster, labelled with its field name and `type_name`,
+/// containing a node for each of its `Pin`s and its children. Devices without children become a
+/// single node with a port for each `Pin`. Every `Wire` becomes edges between the `Pin`s on it.
+/// `Behavioral` devices are drawn as leaf nodes.
+pub fn write_dot(device: &dyn AnyDevice, writer: &mut dyn Write) -> io::Result<()> {
+    write_dot_collapsed(device, writer, &|_, _| false)
+}
+
+/// Writes a `Device` hierarchy as a Graphviz DOT graph, like [`write_dot`], but any device for
+/// which `collapse` returns `true` is drawn as a single node rather than a cluster, hiding
+/// everything inside of it. `collapse` is given each device along with its depth in the hierarchy,
+/// the root device being at depth zero, so e.g. `&|_, depth| depth >= 2` only draws the top two
+/// levels of the hierarchy.
+pub fn write_dot_collapsed(
+    device: &dyn AnyDevice,
+    writer: &mut dyn Write,
+    collapse: &dyn Fn(&dyn AnyDevice, usize) -> bool,
+) -> io::Result<()> {
+    let netlist = Netlist::new(device);
+    let mut graph = Graph {
+        netlist: &netlist,
+        collapse,
+        lines: vec![],
+        endpoints: HashMap::new(),
+    };
+    graph.add_device(0, 0);
+
+    // A wire between two pins is a single edge, anything more gets a junction point.
+    let mut wires: Vec<(&usize, &Vec<String>)> = graph.endpoints.iter().collect();
+    wires.sort();
+    let mut edges = vec![];
+    for (wire, endpoints) in wires.into_iter() {
+        match endpoints.len() {
+            0 | 1 => (),
+            2 => edges.push(format!("{} -- {};", endpoints[0], endpoints[1])),
+            _ => {
+                edges.push(format!("w{} [shape=point];", wire));
+                endpoints
+                    .iter()
+                    .for_each(|endpoint| edges.push(format!("w{} -- {};", wire, endpoint)));
+            }
+        }
+    }
+
+    writeln!(writer, "graph {{")?;
+    writeln!(writer, "  rankdir=LR;")?;
+    writeln!(writer, "  node [shape=record];")?;
+    for line in graph.lines.iter().chain(edges.iter()) {
+        writeln!(writer, "  {}", line)?;
+    }
+    writeln!(writer, "}}")
+}
+
+/// The graph that has been generated so far.
+struct Graph<'a, 'b> {
+    /// The flattened `Device` hierarchy.
+    netlist: &'b Netlist<'a>,
+
+    /// Determines which devices are drawn as a single node.
+    collapse: &'b dyn Fn(&dyn AnyDevice, usize) -> bool,
+
+    /// The nodes and clusters, indented to show the hierarchy.
+    lines: Vec<String>,
+
+    /// The nodes (or node ports) which have been drawn for the `Pin`s on each `Wire`.
+    endpoints: HashMap<usize, Vec<String>>,
+}
+
+impl Graph<'_, '_> {
+    /// Draws a device, as either a cluster or a single node.
+    fn add_device(&mut self, device: usize, depth: usize) {
+        let netlist_device = &self.netlist.get_devices()[device];
+        let label = match netlist_device.get_parent() {
+            Some(_) => format!(
+                "{}: {}",
+                netlist_device.get_name(),
+                netlist_device.get_device().type_name()
+            ),
+            None => netlist_device.get_device().type_name(),
+        };
+        let indent = "  ".repeat(depth);
+
+        if netlist_device.get_children().is_empty()
+            || (self.collapse)(netlist_device.get_device(), depth)
+        {
+            let ports: Vec<String> = netlist_device
+                .get_pins()
+                .iter()
+                .enumerate()
+                .map(|(index, pin)| format!("<p{}> {}", index, escape(pin.get_name())))
+                .collect();
+            self.lines.push(format!(
+                "{}d{} [label=\"{{{}|{{{}}}}}\"];",
+                indent,
+                device,
+                escape(&label),
+                ports.join("|")
+            ));
+            for (index, pin) in netlist_device.get_pins().iter().enumerate() {
+                self.add_endpoint(pin.get_wire(), format!("d{}:p{}", device, index));
+            }
+            return;
+        }
+
+        self.lines
+            .push(format!("{}subgraph cluster_{} {{", indent, device));
+        self.lines.push(format!(
+            "{}  label=\"{}\";",
+            indent,
+            label.replace('"', "\\\"")
+        ));
+        for (index, pin) in netlist_device.get_pins().iter().enumerate() {
+            self.lines.push(format!(
+                "{}  d{}_p{} [shape=plaintext, label=\"{}\"];",
+                indent,
+                device,
+                index,
+                pin.get_name().replace('"', "\\\"")
+            ));
+            self.add_endpoint(pin.get_wire(), format!("d{}_p{}", device, index));
+        }
+        for child in netlist_device.get_children().iter() {
+            self.add_device(*child, depth + 1);
+        }
+        self.lines.push(format!("{}}}", indent));
+    }
+
+    /// Records that a `Pin` on the given `Wire` has been drawn.
+    fn add_endpoint(&mut self, wire: usize, endpoint: String) {
+        self.endpoints.entry(wire).or_default().push(endpoint);
+    }
+}
+
+/// Escapes the characters which have a special meaning in record labels.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "{}|<>\"".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constant, Device, DeviceContainer, Pin, Transistor};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // A transistor which passes its input through when enabled.
+    #[derive(Device)]
+    struct TestSwitch {
+        #[child]
+        strong_true: Constant,
+
+        #[child]
+        nmos: Transistor,
+
+        #[pin]
+        input: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestSwitch {
+        fn new() -> Self {
+            let strong_true = Constant::new_strong(true);
+            let nmos = Transistor::new_nmos();
+            let input = nmos.get_source().clone();
+            let output = nmos.get_drain().clone();
+            Pin::connect(strong_true.get_output(), nmos.get_gate());
+
+            Self {
+                strong_true,
+                nmos,
+                input,
+                output,
+            }
+        }
+    }
+
+    // Two switches in series.
+    #[derive(Device)]
+    struct TestSeries {
+        #[children]
+        switches: Vec<TestSwitch>,
+
+        #[pin]
+        input: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestSeries {
+        fn new() -> Self {
+            let switches = vec![TestSwitch::new(), TestSwitch::new()];
+            let input = switches[0].get_input().clone();
+            let output = switches[1].get_output().clone();
+            Pin::connect(switches[0].get_output(), switches[1].get_input());
+
+            Self {
+                switches,
+                input,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_dot() {
+        let mut output = vec![];
+        write_dot(&TestSwitch::new(), &mut output).unwrap();
+        let expected = "\
+graph {
+  rankdir=LR;
+  node [shape=record];
+  subgraph cluster_0 {
+    label=\"TestSwitch\";
+    d0_p0 [shape=plaintext, label=\"input\"];
+    d0_p1 [shape=plaintext, label=\"output\"];
+    d1 [label=\"{nmos: Transistor|{<p0> drain|<p1> gate|<p2> source}}\"];
+    d2 [label=\"{strong_true: Constant|{<p0> output}}\"];
+  }
+  d0_p0 -- d1:p2;
+  d0_p1 -- d1:p0;
+  d1:p1 -- d2:p0;
+}
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_dot_collapsed() {
+        let mut output = vec![];
+        write_dot_collapsed(&TestSeries::new(), &mut output, &|_, depth| depth >= 1).unwrap();
+        let expected = "\
+graph {
+  rankdir=LR;
+  node [shape=record];
+  subgraph cluster_0 {
+    label=\"TestSeries\";
+    d0_p0 [shape=plaintext, label=\"input\"];
+    d0_p1 [shape=plaintext, label=\"output\"];
+    d1 [label=\"{switches[0]: TestSwitch|{<p0> input|<p1> output}}\"];
+    d4 [label=\"{switches[1]: TestSwitch|{<p0> input|<p1> output}}\"];
+  }
+  d0_p0 -- d1:p0;
+  d0_p1 -- d4:p1;
+  d1:p1 -- d4:p0;
+}
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
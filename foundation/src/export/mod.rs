@@ -0,0 +1,7 @@
+mod dot;
+mod spice;
+mod verilog;
+
+pub use dot::{write_dot, write_dot_collapsed};
+pub use spice::{write_spice, SpiceOptions};
+pub use verilog::{write_verilog, write_verilog_with_primitives, VerilogPrimitive};
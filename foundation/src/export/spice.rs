@@ -0,0 +1,465 @@
+use crate::{AnyDevice, DriveValue, Netlist};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+/// Options controlling how a `Device` hierarchy is written as a SPICE netlist.
+pub struct SpiceOptions {
+    /// The name of the model used for every n-channel `Transistor`.
+    pub nmos_model: String,
+
+    /// The name of the model used for every p-channel `Transistor`.
+    pub pmos_model: String,
+
+    /// Written verbatim after the title, e.g. the `.model` cards for `nmos_model` and
+    /// `pmos_model`, or an `.include` of a process library.
+    pub models: String,
+
+    /// The voltage of the `vdd` rail, and of a true `DriveValue`.
+    pub supply_voltage: f64,
+
+    /// The resistance, in ohms, used for weak `Constant`s and weak `TestPin` drives.
+    pub pull_resistance: f64,
+
+    /// The length of one tick, in seconds.
+    pub tick_duration: f64,
+
+    /// The time, in seconds, it takes a `TestPin` to change from one drive to another.
+    pub transition_time: f64,
+
+    /// The drive of `TestPin`s on each tick, by the hierarchical name of the `TestPin`, e.g.
+    /// `inputs[1]`. `TestPin`s which aren't listed keep their current drive for the whole
+    /// simulation.
+    pub stimulus: HashMap<String, Vec<DriveValue>>,
+}
+
+impl Default for SpiceOptions {
+    fn default() -> Self {
+        Self {
+            nmos_model: "nmos".to_string(),
+            pmos_model: "pmos".to_string(),
+            models: "\
+.model nmos NMOS (LEVEL=1 VTO=0.7 KP=110u)
+.model pmos PMOS (LEVEL=1 VTO=-0.7 KP=50u)
+"
+            .to_string(),
+            supply_voltage: 5.0,
+            pull_resistance: 10e3,
+            tick_duration: 1e-9,
+            transition_time: 10e-12,
+            stimulus: HashMap::new(),
+        }
+    }
+}
+
+/// Writes a `Device` hierarchy as a flat SPICE netlist, so it can be simulated electrically with
+/// tools like ngspice.
+///
+/// `Transistor`s are written as MOSFETs using the models from the options, with their bulk tied to
+/// the appropriate rail. Strong `Constant`s become the `vdd` and ground (`0`) nodes, and weak
+/// `Constant`s become pull resistors to those rails. Nodes are named after the first `Pin` on
+/// each `Wire`, with `[` and `]` replaced since they aren't safe in SPICE vector names.
+///
+/// `TestPin`s become piecewise-linear voltage sources following the stimulus from the options. A
+/// `TestPin` which is only ever strongly driven is a plain voltage source. Otherwise, the voltage
+/// source is connected through voltage-controlled switches, one direct and one through a pull
+/// resistor, so it can also be weakly driven or disconnected (for a high impedance or error drive).
+/// `Clock`s become pulse voltage sources, starting with a rising edge. `Behavioral` devices are
+/// left out, so the nodes they drive are left undriven.
+///
+/// A `.tran` analysis covering the whole stimulus is included, so the file can be run as is. An
+/// `InvalidInput` error is returned if the stimulus names something other than a `TestPin`, or has
+/// no drives for a `TestPin`.
+pub fn write_spice(
+    device: &dyn AnyDevice,
+    options: &SpiceOptions,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let netlist = Netlist::new(device);
+    let mut names = Names::new();
+    let mut cards = vec![];
+
+    // Strong constants are the rails, so their wires are named first.
+    let mut nodes: HashMap<usize, String> = HashMap::new();
+    for constant in netlist.get_constants().iter() {
+        if let DriveValue::Strong(value) = constant.get_constant().get_output().borrow().get_drive()
+        {
+            let rail = if value { "vdd" } else { "0" };
+            nodes
+                .entry(constant.get_output())
+                .or_insert(rail.to_string());
+        }
+    }
+    for (device, netlist_device) in netlist.get_devices().iter().enumerate() {
+        let path = netlist.get_path(device);
+        for pin in netlist_device.get_pins().iter() {
+            nodes
+                .entry(pin.get_wire())
+                .or_insert_with(|| match path.as_str() {
+                    "" => names.add(pin.get_name()),
+                    path => names.add(&format!("{}.{}", path, pin.get_name())),
+                });
+        }
+    }
+
+    let element_name = |device: usize| match netlist.get_path(device).as_str() {
+        "" => netlist.get_devices()[device].get_device().type_name(),
+        path => path.to_string(),
+    };
+
+    for transistor in netlist.get_transistors().iter() {
+        let (model, bulk) = if transistor.get_transistor().get_activation() {
+            (&options.nmos_model, "0")
+        } else {
+            (&options.pmos_model, "vdd")
+        };
+        cards.push(format!(
+            "{} {} {} {} {} {}",
+            names.add(&format!("M{}", element_name(transistor.get_device()))),
+            nodes[&transistor.get_drain()],
+            nodes[&transistor.get_gate()],
+            nodes[&transistor.get_source()],
+            bulk,
+            model
+        ));
+    }
+
+    for constant in netlist.get_constants().iter() {
+        if let DriveValue::Weak(value) = constant.get_constant().get_output().borrow().get_drive() {
+            cards.push(format!(
+                "{} {} {} {}",
+                names.add(&format!("R{}", element_name(constant.get_device()))),
+                nodes[&constant.get_output()],
+                if value { "vdd" } else { "0" },
+                format_number(options.pull_resistance)
+            ));
+        }
+    }
+
+    let mut stimulus_names: HashSet<&String> = options.stimulus.keys().collect();
+    let mut ticks = 1;
+    let mut uses_switches = false;
+    for test_pin in netlist.get_test_pins().iter() {
+        let path = netlist.get_path(test_pin.get_device());
+        let drives = match options.stimulus.get(&path) {
+            Some(drives) if drives.is_empty() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("stimulus for {} must have at least one drive", path),
+                ));
+            }
+            Some(drives) => {
+                stimulus_names.remove(&path);
+                drives.clone()
+            }
+            None => vec![test_pin.get_test_pin().get_output().borrow().get_drive()],
+        };
+        ticks = ticks.max(drives.len());
+
+        let name = element_name(test_pin.get_device());
+        let node = &nodes[&test_pin.get_output()];
+        let level = |drive: &DriveValue| match drive {
+            DriveValue::Strong(true) | DriveValue::Weak(true) => options.supply_voltage,
+            _ => 0.0,
+        };
+        if drives
+            .iter()
+            .all(|drive| matches!(drive, DriveValue::Strong(_)))
+        {
+            let source = names.add(&format!("V{}", name));
+            cards.push(format!(
+                "{} {} 0 {}",
+                source,
+                node,
+                pwl(&drives, options, level)
+            ));
+            continue;
+        }
+
+        uses_switches = true;
+        let level_node = names.add(&format!("{}.level", name));
+        let weak_node = names.add(&format!("{}.weak", name));
+        let strong_control = names.add(&format!("{}.strong_control", name));
+        let weak_control = names.add(&format!("{}.weak_control", name));
+        let control = |strong: bool| {
+            move |drive: &DriveValue| match (drive, strong) {
+                (DriveValue::Strong(_), true) | (DriveValue::Weak(_), false) => 1.0,
+                _ => 0.0,
+            }
+        };
+        cards.push(format!(
+            "{} {} 0 {}",
+            names.add(&format!("V{}", name)),
+            level_node,
+            pwl(&drives, options, level)
+        ));
+        cards.push(format!(
+            "{} {} 0 {}",
+            names.add(&format!("V{}.strong", name)),
+            strong_control,
+            pwl(&drives, options, control(true))
+        ));
+        cards.push(format!(
+            "{} {} 0 {}",
+            names.add(&format!("V{}.weak", name)),
+            weak_control,
+            pwl(&drives, options, control(false))
+        ));
+        cards.push(format!(
+            "{} {} {} {} 0 switch",
+            names.add(&format!("S{}.strong", name)),
+            level_node,
+            node,
+            strong_control
+        ));
+        cards.push(format!(
+            "{} {} {} {}",
+            names.add(&format!("R{}", name)),
+            level_node,
+            weak_node,
+            format_number(options.pull_resistance)
+        ));
+        cards.push(format!(
+            "{} {} {} {} 0 switch",
+            names.add(&format!("S{}.weak", name)),
+            weak_node,
+            node,
+            weak_control
+        ));
+    }
+    for clock in netlist.get_clocks().iter() {
+        let tick_duration = options.tick_duration;
+        let period = clock.get_clock().get_period();
+        ticks = ticks.max(period);
+        cards.push(format!(
+            "{} {} 0 PULSE(0 {} 0 {} {} {} {})",
+            names.add(&format!("V{}", element_name(clock.get_device()))),
+            nodes[&clock.get_output()],
+            format_number(options.supply_voltage),
+            format_number(options.transition_time),
+            format_number(options.transition_time),
+            format_number(clock.get_clock().get_high_ticks() as f64 * tick_duration),
+            format_number(period as f64 * tick_duration)
+        ));
+    }
+
+    if let Some(name) = stimulus_names.into_iter().next() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "stimulus must only be given for TestPins, but {} is not one",
+                name
+            ),
+        ));
+    }
+
+    writeln!(writer, "* {}", device.type_name())?;
+    write!(writer, "{}", options.models)?;
+    if uses_switches {
+        writeln!(writer, ".model switch SW (VT=0.5 RON=1m ROFF=1T)")?;
+    }
+    writeln!(
+        writer,
+        "Vdd vdd 0 DC {}",
+        format_number(options.supply_voltage)
+    )?;
+    for card in cards.iter() {
+        writeln!(writer, "{}", card)?;
+    }
+    writeln!(
+        writer,
+        ".tran {} {}",
+        format_number(options.transition_time),
+        format_number(ticks as f64 * options.tick_duration)
+    )?;
+    writeln!(writer, ".end")
+}
+
+/// Keeps track of the node and element names which have been used, so they're all distinct.
+struct Names {
+    /// Every name used so far, in lowercase since SPICE names are case insensitive.
+    used: HashSet<String>,
+}
+
+impl Names {
+    /// Creates a new set of names, with the rails already used.
+    fn new() -> Self {
+        Self {
+            used: ["0", "vdd"].iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Converts a hierarchical name into a SPICE name which hasn't been used yet, and marks it as
+    /// used.
+    fn add(&mut self, name: &str) -> String {
+        let name: String = name
+            .replace(']', "")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut unique = name.clone();
+        let mut suffix = 1;
+        while !self.used.insert(unique.to_lowercase()) {
+            unique = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        unique
+    }
+}
+
+/// Formats a piecewise-linear source which has the value of `value` for each drive in turn, with
+/// one drive per tick.
+fn pwl(
+    drives: &[DriveValue],
+    options: &SpiceOptions,
+    value: impl Fn(&DriveValue) -> f64,
+) -> String {
+    let mut points = vec![(0.0, value(&drives[0]))];
+    for (tick, pair) in drives.windows(2).enumerate() {
+        let (previous, next) = (value(&pair[0]), value(&pair[1]));
+        if previous != next {
+            let time = (tick + 1) as f64 * options.tick_duration;
+            points.push((time, previous));
+            points.push((time + options.transition_time, next));
+        }
+    }
+    let points: Vec<String> = points
+        .into_iter()
+        .map(|(time, value)| format!("{} {}", format_number(time), format_number(value)))
+        .collect();
+    format!("PWL({})", points.join(" "))
+}
+
+/// Formats a number for SPICE in scientific notation, without any floating point noise.
+fn format_number(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_string();
+    }
+    let formatted = format!("{:.9e}", number);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+    match exponent {
+        "0" => mantissa.to_string(),
+        _ => format!("{}e{}", mantissa, exponent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TestNot;
+    use crate::{AnyDevice, Device, DeviceContainer, Pin, TestPin, Transistor};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // A NOT gate with a weak pull-down on the output, driven by a TestPin.
+    #[derive(Device)]
+    struct TestDevice {
+        #[child]
+        input: TestPin,
+
+        #[child]
+        not_gate: TestNot,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestDevice {
+        fn new() -> Self {
+            let input = TestPin::new(DriveValue::Strong(false));
+            let not_gate = TestNot::with_weak_pull_down();
+            let output = not_gate.get_output().clone();
+            Pin::connect(input.get_output(), not_gate.get_input());
+            Self {
+                input,
+                not_gate,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_spice() {
+        let device = TestDevice::new();
+        let mut options = SpiceOptions::default();
+        options.stimulus.insert(
+            "input".to_string(),
+            vec![DriveValue::Strong(false), DriveValue::Strong(true)],
+        );
+
+        let mut output = vec![];
+        write_spice(&device, &options, &mut output).unwrap();
+        let expected = "\
+* TestDevice
+.model nmos NMOS (LEVEL=1 VTO=0.7 KP=110u)
+.model pmos PMOS (LEVEL=1 VTO=-0.7 KP=50u)
+Vdd vdd 0 DC 5
+Mnot_gate.nmos output input.output 0 0 nmos
+Mnot_gate.pmos output input.output vdd vdd pmos
+Rnot_gate.weak_false_0 output 0 1e4
+Vinput input.output 0 PWL(0 0 1e-9 0 1.01e-9 5)
+.tran 1e-11 2e-9
+.end
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_spice_switches() {
+        let test_pin = TestPin::new(DriveValue::Weak(true));
+        let mut options = SpiceOptions::default();
+        options.stimulus.insert(
+            "".to_string(),
+            vec![
+                DriveValue::Weak(true),
+                DriveValue::Strong(false),
+                DriveValue::HighImpedance,
+            ],
+        );
+
+        let mut output = vec![];
+        write_spice(&test_pin, &options, &mut output).unwrap();
+        let expected = "\
+* TestPin
+.model nmos NMOS (LEVEL=1 VTO=0.7 KP=110u)
+.model pmos PMOS (LEVEL=1 VTO=-0.7 KP=50u)
+.model switch SW (VT=0.5 RON=1m ROFF=1T)
+Vdd vdd 0 DC 5
+VTestPin TestPin.level 0 PWL(0 5 1e-9 5 1.01e-9 0)
+VTestPin.strong TestPin.strong_control 0 PWL(0 0 1e-9 0 1.01e-9 1 2e-9 1 2.01e-9 0)
+VTestPin.weak TestPin.weak_control 0 PWL(0 1 1e-9 1 1.01e-9 0)
+STestPin.strong TestPin.level output TestPin.strong_control 0 switch
+RTestPin TestPin.level TestPin.weak 1e4
+STestPin.weak TestPin.weak output TestPin.weak_control 0 switch
+.tran 1e-11 3e-9
+.end
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_spice_bad_stimulus() {
+        let mut options = SpiceOptions::default();
+        options
+            .stimulus
+            .insert("nmos".to_string(), vec![DriveValue::Strong(true)]);
+        let error = write_spice(&Transistor::new_nmos(), &options, &mut vec![]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_write_spice_empty_stimulus() {
+        let mut options = SpiceOptions::default();
+        options.stimulus.insert("".to_string(), vec![]);
+        let test_pin = TestPin::new(DriveValue::Strong(true));
+        let error = write_spice(&test_pin, &options, &mut vec![]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{AnyDevice, DriveValue, Netlist, NetlistPin};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+/// Writes a `Device` hierarchy as structural Verilog.
+///
+/// One module is written for each distinct device type, named after its `type_name`. Devices of
+/// the same type can still differ in structure (e.g. a `NandGate` with two or three inputs), in
+/// which case the variants get a numeric suffix. Every `Pin` becomes an `inout` port, since pins
+/// have no direction, and every child device becomes an instance.
+///
+/// `Transistor`s are written as `nmos` and `pmos` switch primitives, strong `Constant`s as
+/// `supply1` and `supply0` nets, and weak `Constant`s as `pullup` and `pulldown` primitives.
+/// `TestPin`s and `Clock`s have no Verilog equivalent, so the nets they are connected to are left
+/// undriven. `Behavioral` devices become empty modules with only their ports, which leaves the nets
+/// they drive undriven as well.
+pub fn write_verilog(device: &dyn AnyDevice, writer: &mut dyn Write) -> io::Result<()> {
+    write_verilog_with_primitives(device, writer, &|_| None)
+}
+
+/// A device which should be written as a Verilog primitive, rather than as a module instance.
+pub struct VerilogPrimitive {
+    /// The Verilog primitive, e.g. `and` or `bufif1`.
+    pub primitive: &'static str,
+
+    /// The names of the device's `Pin`s to connect to the terminals of the primitive, in order.
+    pub pins: Vec<String>,
+}
+
+/// Writes a `Device` hierarchy as structural Verilog, like [`write_verilog`], but stops at any
+/// device for which `primitive` returns a `VerilogPrimitive`, writing that primitive instead of
+/// descending into the device. This allows other crates to write their devices as Verilog gate
+/// primitives rather than transistors.
+pub fn write_verilog_with_primitives(
+    device: &dyn AnyDevice,
+    writer: &mut dyn Write,
+    primitive: &dyn Fn(&dyn AnyDevice) -> Option<VerilogPrimitive>,
+) -> io::Result<()> {
+    let netlist = Netlist::new(device);
+    let mut modules = Modules::new(&netlist, primitive);
+    modules.add_module(0);
+    modules
+        .texts
+        .iter()
+        .try_for_each(|text| writer.write_all(text.as_bytes()))
+}
+
+/// The modules that have been generated so far.
+struct Modules<'a, 'b> {
+    /// The flattened `Device` hierarchy.
+    netlist: &'b Netlist<'a>,
+
+    /// Determines which devices should be written as Verilog primitives.
+    primitive: &'b dyn Fn(&dyn AnyDevice) -> Option<VerilogPrimitive>,
+
+    /// The index of the `Transistor` for each device that is a `Transistor`.
+    transistors: HashMap<usize, usize>,
+
+    /// The index of the `Constant` for each device that is a `Constant`.
+    constants: HashMap<usize, usize>,
+
+    /// The devices which are `TestPin`s or `Clock`s.
+    stimulus: HashSet<usize>,
+
+    /// The text of every module generated so far, with dependencies first.
+    texts: Vec<String>,
+
+    /// Maps the body of every module generated so far to its name, so identical modules are only
+    /// written once.
+    names: HashMap<String, String>,
+
+    /// The number of distinct modules generated so far for each device type.
+    variants: HashMap<String, usize>,
+}
+
+impl<'a, 'b> Modules<'a, 'b> {
+    /// Creates a new, empty, set of modules.
+    fn new(
+        netlist: &'b Netlist<'a>,
+        primitive: &'b dyn Fn(&dyn AnyDevice) -> Option<VerilogPrimitive>,
+    ) -> Self {
+        Self {
+            netlist,
+            primitive,
+            transistors: netlist
+                .get_transistors()
+                .iter()
+                .enumerate()
+                .map(|(index, transistor)| (transistor.get_device(), index))
+                .collect(),
+            constants: netlist
+                .get_constants()
+                .iter()
+                .enumerate()
+                .map(|(index, constant)| (constant.get_device(), index))
+                .collect(),
+            stimulus: netlist
+                .get_test_pins()
+                .iter()
+                .map(|test_pin| test_pin.get_device())
+                .chain(netlist.get_clocks().iter().map(|clock| clock.get_device()))
+                .collect(),
+            texts: vec![],
+            names: HashMap::new(),
+            variants: HashMap::new(),
+        }
+    }
+
+    /// Generates the module for a device, and all of the modules it depends on, if they haven't
+    /// been generated already. Returns the name of the module.
+    fn add_module(&mut self, device: usize) -> String {
+        let netlist_device = &self.netlist.get_devices()[device];
+        let mut nets = Nets::new(netlist_device.get_pins().iter().map(|pin| pin.get_name()));
+        nets.used
+            .extend(self.children(device).map(|(_, name)| name));
+        let mut declarations = vec![];
+        let mut statements = vec![];
+
+        // Ports. The first port on each wire names the net, any others are connected to it.
+        for pin in netlist_device.get_pins().iter() {
+            let reference = reference(pin.get_name());
+            match nets.names.get(&pin.get_wire()) {
+                Some(net) => statements.push(format!("tran ({}, {});", net, reference)),
+                None => {
+                    nets.names.insert(pin.get_wire(), reference);
+                }
+            }
+        }
+        for (base, width) in ports(netlist_device.get_pins()) {
+            match width {
+                Some(width) => {
+                    declarations.push(format!("inout [{}:0] {};", width - 1, identifier(&base)))
+                }
+                None => declarations.push(format!("inout {};", identifier(&base))),
+            }
+        }
+
+        // Constants name their net, unless it was already named by a port.
+        let children: Vec<(usize, String)> = self.children(device).collect();
+        for (child, name) in children.iter() {
+            if let Some(constant) = self.constants.get(child) {
+                let netlist_constant = &self.netlist.get_constants()[*constant];
+                let net = identifier(name);
+                match netlist_constant
+                    .get_constant()
+                    .get_output()
+                    .borrow()
+                    .get_drive()
+                {
+                    DriveValue::Strong(value) => {
+                        declarations.push(format!("supply{} {};", u8::from(value), net));
+                        match nets.names.get(&netlist_constant.get_output()) {
+                            Some(existing) => {
+                                statements.push(format!("tran ({}, {});", existing, net))
+                            }
+                            None => {
+                                nets.names.insert(netlist_constant.get_output(), net);
+                            }
+                        }
+                    }
+                    DriveValue::Weak(value) => {
+                        let primitive = if value { "pullup" } else { "pulldown" };
+                        let output = nets.get(netlist_constant.get_output());
+                        statements.push(format!("{} {} ({});", primitive, net, output));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // Everything else is an instance.
+        for (child, name) in children.iter() {
+            let instance = identifier(name);
+            if let Some(transistor) = self.transistors.get(child) {
+                let netlist_transistor = &self.netlist.get_transistors()[*transistor];
+                let primitive = if netlist_transistor.get_transistor().get_activation() {
+                    "nmos"
+                } else {
+                    "pmos"
+                };
+                statements.push(format!(
+                    "{} {} ({}, {}, {});",
+                    primitive,
+                    instance,
+                    nets.get(netlist_transistor.get_drain()),
+                    nets.get(netlist_transistor.get_source()),
+                    nets.get(netlist_transistor.get_gate()),
+                ));
+            } else if self.stimulus.contains(child) {
+                let type_name = self.netlist.get_devices()[*child].get_device().type_name();
+                statements.push(format!("// {} {} is not synthesizable.", type_name, name));
+            } else if let Some(primitive) =
+                (self.primitive)(self.netlist.get_devices()[*child].get_device())
+            {
+                let pins = self.netlist.get_devices()[*child].get_pins();
+                let terminals: Vec<String> = primitive
+                    .pins
+                    .iter()
+                    .map(|name| {
+                        let pin = pins.iter().find(|pin| pin.get_name() == *name).unwrap();
+                        nets.get(pin.get_wire())
+                    })
+                    .collect();
+                statements.push(format!(
+                    "{} {} ({});",
+                    primitive.primitive,
+                    instance,
+                    terminals.join(", ")
+                ));
+            } else if !self.constants.contains_key(child) {
+                let module = self.add_module(*child);
+                let pins = self.netlist.get_devices()[*child].get_pins();
+                let connections: Vec<String> = ports(pins)
+                    .into_iter()
+                    .map(|(base, width)| {
+                        let net = match width {
+                            Some(width) => {
+                                let bits: Vec<String> = (0..width)
+                                    .rev()
+                                    .map(|bit| {
+                                        let name = format!("{}[{}]", base, bit);
+                                        let pin =
+                                            pins.iter().find(|pin| pin.get_name() == name).unwrap();
+                                        nets.get(pin.get_wire())
+                                    })
+                                    .collect();
+                                format!("{{{}}}", bits.join(", "))
+                            }
+                            None => {
+                                let pin = pins.iter().find(|pin| pin.get_name() == base).unwrap();
+                                nets.get(pin.get_wire())
+                            }
+                        };
+                        format!(".{}({})", identifier(&base), net)
+                    })
+                    .collect();
+                statements.push(format!(
+                    "{} {} ({});",
+                    identifier(&module),
+                    instance,
+                    connections.join(", ")
+                ));
+            }
+        }
+
+        // Any nets that weren't named by a port or constant are internal wires.
+        let mut wires: Vec<&String> = nets.generated.iter().collect();
+        wires.sort_by_key(|net| net[1..].parse::<usize>().unwrap());
+        wires
+            .into_iter()
+            .for_each(|net| declarations.push(format!("wire {};", net)));
+
+        let port_names: Vec<String> = ports(netlist_device.get_pins())
+            .into_iter()
+            .map(|(base, _)| identifier(&base))
+            .collect();
+        let mut body = format!("({});\n", port_names.join(", "));
+        declarations
+            .iter()
+            .chain(statements.iter())
+            .for_each(|line| body.push_str(&format!("  {}\n", line)));
+        body.push_str("endmodule\n");
+
+        if let Some(name) = self.names.get(&body) {
+            return name.clone();
+        }
+
+        let type_name = netlist_device.get_device().type_name();
+        let variant = self.variants.entry(type_name.clone()).or_insert(0);
+        let name = match *variant {
+            0 => type_name,
+            variant => format!("{}_{}", type_name, variant),
+        };
+        *variant += 1;
+        self.texts
+            .push(format!("module {} {}\n", identifier(&name), body));
+        self.names.insert(body, name.clone());
+        name
+    }
+
+    /// Gets the children of a device, along with their names.
+    fn children(&self, device: usize) -> impl Iterator<Item = (usize, String)> + '_ {
+        self.netlist.get_devices()[device]
+            .get_children()
+            .iter()
+            .map(|child| {
+                (
+                    *child,
+                    self.netlist.get_devices()[*child].get_name().to_string(),
+                )
+            })
+    }
+}
+
+/// The names of the nets within a single module.
+struct Nets {
+    /// The name of the net for each `Wire` ID.
+    names: HashMap<usize, String>,
+
+    /// The names which were generated for internal wires.
+    generated: HashSet<String>,
+
+    /// Names which are already used for ports and instances within the module.
+    used: HashSet<String>,
+}
+
+impl Nets {
+    /// Creates a new set of nets, which avoids clashing with the given port names.
+    fn new<'c>(ports: impl Iterator<Item = &'c str>) -> Self {
+        Self {
+            names: HashMap::new(),
+            generated: HashSet::new(),
+            used: ports.map(|port| port.to_string()).collect(),
+        }
+    }
+
+    /// Gets the name of the net for the given `Wire` ID, generating a name if there isn't one.
+    fn get(&mut self, wire: usize) -> String {
+        if let Some(name) = self.names.get(&wire) {
+            return name.clone();
+        }
+
+        let mut index = self.generated.len();
+        let mut name = format!("w{}", index);
+        while self.used.contains(&name) {
+            index += 1;
+            name = format!("w{}", index);
+        }
+        self.used.insert(name.clone());
+        self.generated.insert(name.clone());
+        self.names.insert(wire, name.clone());
+        name
+    }
+}
+
+/// Groups the `Pin`s of a device into ports. Returns the name of each port, along with its width
+/// if it is a vector.
+fn ports(pins: &[NetlistPin]) -> Vec<(String, Option<usize>)> {
+    let mut ports: Vec<(String, Option<usize>)> = vec![];
+    for pin in pins.iter() {
+        match pin.get_name().split_once('[') {
+            Some((base, _)) => match ports.last_mut() {
+                Some((last, Some(width))) if last == base => *width += 1,
+                _ => ports.push((base.to_string(), Some(1))),
+            },
+            None => ports.push((pin.get_name().to_string(), None)),
+        }
+    }
+    ports
+}
+
+/// Converts a `Pin` name, e.g. `input[1]`, into a Verilog reference to the port or bit.
+fn reference(name: &str) -> String {
+    match name.split_once('[') {
+        Some((base, index)) => format!("{}[{}", identifier(base), index),
+        None => identifier(name),
+    }
+}
+
+/// Converts a name into a Verilog identifier, using an escaped identifier if necessary.
+fn identifier(name: &str) -> String {
+    let simple = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name);
+    if simple {
+        name.to_string()
+    } else {
+        format!("\\{} ", name)
+    }
+}
+
+/// Verilog keywords which can't be used as identifiers without escaping them.
+const KEYWORDS: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "begin",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "cmos",
+    "default",
+    "else",
+    "end",
+    "endmodule",
+    "for",
+    "function",
+    "if",
+    "initial",
+    "inout",
+    "input",
+    "integer",
+    "module",
+    "nand",
+    "nmos",
+    "nor",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "pmos",
+    "pulldown",
+    "pullup",
+    "reg",
+    "supply0",
+    "supply1",
+    "task",
+    "tran",
+    "tri",
+    "wire",
+    "xnor",
+    "xor",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TestNot;
+    use crate::{Device, DeviceContainer, Pin};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Two NOT gates in series, plus an extra internal wire.
+    #[derive(Device)]
+    struct TestBuffer {
+        #[children]
+        not_gates: Vec<TestNot>,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    #[test]
+    fn test_write_verilog() {
+        let not_gates = vec![
+            TestNot::with_weak_pull_down(),
+            TestNot::with_weak_pull_down(),
+        ];
+        let input = vec![
+            not_gates[0].get_input().clone(),
+            not_gates[0].get_input().clone(),
+        ];
+        let output = not_gates[1].get_output().clone();
+        Pin::connect(not_gates[0].get_output(), not_gates[1].get_input());
+        let buffer = TestBuffer {
+            not_gates,
+            input,
+            output,
+        };
+
+        let mut output = vec![];
+        write_verilog(&buffer, &mut output).unwrap();
+        let expected = "\
+module TestNot (\\input , \\output );
+  inout \\input ;
+  inout \\output ;
+  supply0 strong_false;
+  supply1 strong_true;
+  pulldown \\weak_false[0]  (\\output );
+  nmos \\nmos  (\\output , strong_false, \\input );
+  pmos \\pmos  (\\output , strong_true, \\input );
+endmodule
+
+module TestBuffer (\\input , \\output );
+  inout [1:0] \\input ;
+  inout \\output ;
+  wire w0;
+  tran (\\input [0], \\input [1]);
+  TestNot \\not_gates[0]  (.\\input (\\input [0]), .\\output (w0));
+  TestNot \\not_gates[1]  (.\\input (w0), .\\output (\\output ));
+endmodule
+
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
@@ -4,10 +4,11 @@
 //! # Overview
 //!
 //! This crate contains the full simulation framework, and the code is kept rather minimal to keep
-//! the logic as clear as possible. The other crates build upon this crate, but all they can really
-//! do is provide a function that arranges transistors in a certain way, but they cannot play any
+//! the logic as clear as possible. The other crates build upon this crate, but for the most part
+//! all they do is provide a function that arranges transistors in a certain way, and they play no
 //! active role in the simulation. That is to say, there's no magic, it's really just transistors
-//! connected together following a fairly simple set of rules.
+//! connected together following a fairly simple set of rules. The one exception is that a device
+//! can opt in to being [`Behavioral`], see below.
 //!
 //! # Concepts
 //!
@@ -51,12 +52,13 @@
 //! single [`LogicValue`] that the `Wire` resolves to. Since `Wire` is hidden, the [`Pin::read`]
 //! function is used to get the [`LogicValue`] of the underlying `Wire`.
 //!
-//! Note that [`Pin`]s cannot be created outside of this crate, and there are no functions other
-//! than [`Pin::connect`] that mutate pins. So, for the most part, your interact with [`Pin`]s will
-//! be merely connecting them and passing them along through the hierarchy. Because [`Pin`]s must
-//! be mutable to connect them, and because `Wire`s need to keep a list of `Weak` pointers to their
-//! constituent `[Pin]`s (in order to update them when connections are made), we must use
-//! `Rc<RefCell<Pin>>` everywhere rather than nicer references, unfortunately.
+//! Note that [`Pin`]s are normally only created by the primitives of this crate, and there are no
+//! public functions other than [`Pin::connect`] that mutate pins. So, for the most part, your
+//! interact with [`Pin`]s will be merely connecting them and passing them along through the
+//! hierarchy. Because [`Pin`]s must be mutable to connect them, and because `Wire`s need to keep a
+//! list of `Weak` pointers to their constituent `[Pin]`s (in order to update them when
+//! connections are made), we must use `Rc<RefCell<Pin>>` everywhere rather than nicer references,
+//! unfortunately.
 //!
 //! ## Device
 //!
@@ -69,8 +71,8 @@
 //! This is incredibly generic, and can represent basically any device in an electronic system.
 //! Since this definition is recursive, i.e. a [`Device`] can contain other [`Device`]s, then we
 //! will need some initial "primitive" devices which consist only of [`Pin`]s, to provide a way to
-//! terminate this recursion, since [`Pin`]s cannot be created outside of this crate. That is
-//! exactly what the `primitive` module does, documented below.
+//! terminate this recursion, since the simulation needs to know how to drive their [`Pin`]s. That
+//! is exactly what the `primitive` module does, documented below.
 //!
 //! Note, the [`Device`] trait is cumbersome to implement by hand. A derive procedural macro is
 //! provided at [`device_derive::Device`] which does most of the hard work for you.
@@ -83,7 +85,7 @@
 //! 1. Synthesize the circuit to a Fritzing diagram, so it can be constructed on a breadboard.
 //! 2. Synthesize the circuit to a KiCad PCB that can be printed, ordered, populated, and tested.
 //! 3. Synthesize the circuit to an FPGA.
-//! 4. Count the number of transistors in a circuit.
+//! 4. Count the number of transistors in a circuit, which is what [`ResourceReport`] does.
 //! 5. Other wild stuff!
 //!
 //! A [`DeviceContainer`] `enum` exists, that allows a [`Device`] to store one of or multiple of a
@@ -97,7 +99,7 @@
 //!
 //! ## Primitives
 //!
-//! There are three "primitive" [`Device`]s, i.e. [`Device`]s consisting only of [`Pin`]s.
+//! There are four "primitive" [`Device`]s, i.e. [`Device`]s consisting only of [`Pin`]s.
 //!
 //! ### Constant
 //!
@@ -132,12 +134,61 @@
 //! in tests, but it could synthesize to a header if you want the [`TestPin`] to remain in a
 //! physical design.
 //!
+//! A [`TestBus`] connects a [`TestPin`] to each of a group of [`Pin`]s, e.g. the inputs of an
+//! adder, so the group can be driven with an integer, and [`read_bus`] reads a group of [`Pin`]s
+//! back as an integer.
+//!
+//! ### Clock
+//!
+//! A [`Clock`] is a very simple [`Device`] with a single [`Pin`] which alternates between
+//! [`DriveValue::Strong`] `false` and `true`, with configurable high and low periods in ticks. It
+//! is driven by [`Simulator::run_clock`], which runs a [`Device`] for a number of clock cycles and
+//! calls back on every rising and falling edge, so sequential logic can be tested.
+//!
 //! ## Simulation
 //!
 //! The `simulation` module provides the [`print()`], [`settle`], and [`tick`] functions, all
 //! accepting a [`Device`]. The [`tick`] function moves forward one time step. The [`settle`]
-//! function moves forward until the circuit stops changing. The [`print()`] function is for
-//! debugging, and prints a very detailed representation of the [`Device`].
+//! function moves forward until the circuit stops changing, and the [`settle_with_limit`] function
+//! does the same but gives up, reporting which `Wire`s are toggling, if the circuit oscillates. The
+//! [`print()`] function is for debugging, and prints a very detailed representation of the
+//! [`Device`]. A [`Snapshot`] captures the same information with stable IDs, and can be written as
+//! JSON or YAML so circuit states can be compared between runs.
+//!
+//! Under the hood, [`tick`] and [`settle`] use a [`Simulator`], which flattens the [`Device`]
+//! hierarchy once and then only ticks the [`Transistor`]s whose inputs changed on the previous
+//! tick. If you need to tick the same [`Device`] many times, e.g. while applying stimulus with
+//! [`TestPin`]s, create a [`Simulator`] yourself to avoid flattening the hierarchy repeatedly.
+//!
+//! A [`Device`] can also opt in to being [`Behavioral`], by implementing the trait and marking the
+//! `struct` `#[behavioral]` when deriving [`Device`]. Rather than being built from [`Transistor`]s,
+//! it creates its own [`Pin`]s with [`Pin::new`], and the [`Simulator`] evaluates it each tick its
+//! inputs change to compute the drives of its outputs. This makes it possible to swap a large
+//! [`Device`], like a memory, for a much faster model with exactly the same [`Pin`]s.
+//!
+//! To check that two [`Device`]s with the same [`Pin`]s behave the same way, e.g. a new adder and
+//! an existing one, or a [`Transistor`]-level [`Device`] and a [`Behavioral`] model of it, use
+//! [`check_equivalence`]. It drives the same inputs into both, and reports the first output which
+//! differs as a [`Divergence`]. To check a [`Device`] against its truth table instead, use
+//! [`check_truth_table`], which drives every combination of booleans or of [`DRIVE_VALUES`] onto
+//! the inputs and reports every output which doesn't match. For arithmetic, where the expected
+//! outputs are easier to describe as integers, use [`check_integer_truth_table`].
+//!
+//! A [`VcdRecorder`] can be used in place of a [`Simulator`] to record the value of every [`Pin`]
+//! on every tick, and write them to a Value Change Dump file which can be viewed with GTKWave.
+//!
+//! ## Netlist
+//!
+//! Tools which need to know how everything is connected, rather than just simulate it, can build a
+//! [`Netlist`] from any [`Device`]. It flattens the hierarchy and assigns stable integer IDs to
+//! every device, every primitive, and every `Wire`, so connectivity doesn't have to be re-derived
+//! from [`Pin`] pointers by every tool.
+//!
+//! The `export` module uses the [`Netlist`] to write a [`Device`] out in formats other tools can
+//! read. [`write_verilog`] writes structural Verilog, so circuits can be checked with tools like
+//! Icarus Verilog or Yosys. [`write_spice`] writes a SPICE netlist, so the transistor topologies
+//! can be validated electrically with tools like ngspice. [`write_dot`] writes a Graphviz graph of
+//! the hierarchy and how everything is wired together.
 //!
 //! # Usage
 //!
@@ -237,20 +288,39 @@
 //! digital electronics seems like a pretty good place.
 
 #![feature(stmt_expr_attributes)]
-#![feature(strict_overflow_ops)]
-#![feature(trait_upcasting)]
 #![deny(missing_docs)]
 
 // Modules.
+mod analysis;
 mod device;
+mod export;
+mod netlist;
 mod pin;
 mod primitive;
 mod simulation;
+#[cfg(test)]
+mod test_fixtures;
 mod value;
 
 // Re-exports.
+pub use analysis::{
+    check_equivalence, check_integer_truth_table, check_truth_table, read_bus, Divergence,
+    EquivalenceOptions, ResourceCount, ResourceReport, ResourceSubtotal, TestBus, TimingAnalysis,
+    TimingPath, TruthTableMismatch, TruthTableValues,
+};
 pub use device::{AnyDevice, Device, DeviceContainer};
+pub use export::{
+    write_dot, write_dot_collapsed, write_spice, write_verilog, write_verilog_with_primitives,
+    SpiceOptions, VerilogPrimitive,
+};
+pub use netlist::{
+    Netlist, NetlistClock, NetlistConstant, NetlistDevice, NetlistPin, NetlistTestPin,
+    NetlistTransistor, NetlistWire, Terminal,
+};
 pub use pin::Pin;
-pub use primitive::{Constant, TestPin, Transistor};
-pub use simulation::{print, settle, tick};
+pub use primitive::{Clock, Constant, TestPin, Transistor};
+pub use simulation::{
+    print, settle, settle_with_limit, tick, Behavioral, ClockEdge, ClockMode, SettleError,
+    Simulator, Snapshot, SnapshotDevice, SnapshotPin, VcdRecorder,
+};
 pub use value::{DriveValue, LogicValue, DRIVE_VALUES};
@@ -0,0 +1,721 @@
+use crate::{AnyDevice, Clock, Constant, DeviceContainer, LogicValue, Pin, TestPin, Transistor};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A flattened, canonical representation of a `Device` hierarchy.
+///
+/// Every device in the hierarchy, every primitive (`Transistor`, `Constant`, `TestPin`, and
+/// `Clock`), and every `Wire` is assigned an integer ID, which is simply its index into the
+/// corresponding collection. The hierarchy is walked in a sorted order, with a device's `Pin`s
+/// visited before its children, so the IDs are stable as long as the `Device` is constructed the
+/// same way.
+///
+/// The `Netlist` is a snapshot of the connectivity of the `Device` when it was created. Connecting
+/// `Pin`s afterward is not reflected in the `Netlist`, create a new one instead.
+pub struct Netlist<'a> {
+    /// Every device in the hierarchy, the root device being first.
+    devices: Vec<NetlistDevice<'a>>,
+
+    /// Every `Transistor` in the hierarchy.
+    transistors: Vec<NetlistTransistor<'a>>,
+
+    /// Every `Constant` in the hierarchy.
+    constants: Vec<NetlistConstant<'a>>,
+
+    /// Every `TestPin` in the hierarchy.
+    test_pins: Vec<NetlistTestPin<'a>>,
+
+    /// Every `Clock` in the hierarchy.
+    clocks: Vec<NetlistClock<'a>>,
+
+    /// Every `Wire` any of the `Pin`s in the hierarchy are connected to.
+    wires: Vec<NetlistWire>,
+
+    /// Maps every `Pin` on any of the `wires` to the ID of its `Wire`.
+    wire_indices: HashMap<*const Pin, usize>,
+}
+
+/// A device in a `Netlist`.
+pub struct NetlistDevice<'a> {
+    /// The name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    name: String,
+
+    /// The device itself.
+    device: &'a dyn AnyDevice,
+
+    /// The ID of the parent device, or `None` for the root device.
+    parent: Option<usize>,
+
+    /// The IDs of the child devices, sorted by name.
+    children: Vec<usize>,
+
+    /// The `Pin`s of the device, sorted by name.
+    pins: Vec<NetlistPin>,
+}
+
+/// A named `Pin` of a `NetlistDevice`.
+pub struct NetlistPin {
+    /// The name of the `Pin` within its device, e.g. `output` or `input[1]`.
+    name: String,
+
+    /// The `Pin` itself.
+    pin: Rc<RefCell<Pin>>,
+
+    /// The ID of the `Wire` the `Pin` is connected to.
+    wire: usize,
+}
+
+/// A `Transistor` in a `Netlist`.
+pub struct NetlistTransistor<'a> {
+    /// The ID of the `NetlistDevice` for the `Transistor`.
+    device: usize,
+
+    /// The `Transistor` itself.
+    transistor: &'a Transistor,
+
+    /// The ID of the `Wire` the gate `Pin` is connected to.
+    gate: usize,
+
+    /// The ID of the `Wire` the source `Pin` is connected to.
+    source: usize,
+
+    /// The ID of the `Wire` the drain `Pin` is connected to.
+    drain: usize,
+}
+
+/// A `Constant` in a `Netlist`.
+pub struct NetlistConstant<'a> {
+    /// The ID of the `NetlistDevice` for the `Constant`.
+    device: usize,
+
+    /// The `Constant` itself.
+    constant: &'a Constant,
+
+    /// The ID of the `Wire` the output `Pin` is connected to.
+    output: usize,
+}
+
+/// A `TestPin` in a `Netlist`.
+pub struct NetlistTestPin<'a> {
+    /// The ID of the `NetlistDevice` for the `TestPin`.
+    device: usize,
+
+    /// The `TestPin` itself.
+    test_pin: &'a TestPin,
+
+    /// The ID of the `Wire` the output `Pin` is connected to.
+    output: usize,
+}
+
+/// A `Clock` in a `Netlist`.
+pub struct NetlistClock<'a> {
+    /// The ID of the `NetlistDevice` for the `Clock`.
+    device: usize,
+
+    /// The `Clock` itself.
+    clock: &'a Clock,
+
+    /// The ID of the `Wire` the output `Pin` is on.
+    output: usize,
+}
+
+/// A `Wire` in a `Netlist`.
+pub struct NetlistWire {
+    /// All of the `Pin`s connected to the `Wire`.
+    pins: Vec<Rc<RefCell<Pin>>>,
+
+    /// What each of the `pins` belongs to, in the same order.
+    terminals: Vec<Terminal>,
+}
+
+/// Identifies what a `Pin` on a `NetlistWire` belongs to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Terminal {
+    /// The gate `Pin` of the `Transistor` with the given ID.
+    Gate(usize),
+
+    /// The source `Pin` of the `Transistor` with the given ID.
+    Source(usize),
+
+    /// The drain `Pin` of the `Transistor` with the given ID.
+    Drain(usize),
+
+    /// The output `Pin` of the `Constant` with the given ID.
+    Constant(usize),
+
+    /// The output `Pin` of the `TestPin` with the given ID.
+    TestPin(usize),
+
+    /// The output `Pin` of the `Clock` with the given ID.
+    Clock(usize),
+
+    /// A `Pin` of the `Behavioral` device with the given device ID, which may drive the `Wire`.
+    Behavioral(usize),
+
+    /// A `Pin` which is not part of the `Device` hierarchy at all.
+    External,
+}
+
+impl<'a> NetlistDevice<'a> {
+    /// Gets the name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the device itself.
+    pub fn get_device(&self) -> &'a dyn AnyDevice {
+        self.device
+    }
+
+    /// Gets the ID of the parent device, or `None` for the root device.
+    pub fn get_parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Gets the IDs of the child devices, sorted by name.
+    pub fn get_children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Gets the `Pin`s of the device, sorted by name.
+    pub fn get_pins(&self) -> &[NetlistPin] {
+        &self.pins
+    }
+}
+
+impl NetlistPin {
+    /// Gets the name of the `Pin` within its device, e.g. `output` or `input[1]`.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the `Pin` itself.
+    pub fn get_pin(&self) -> &Rc<RefCell<Pin>> {
+        &self.pin
+    }
+
+    /// Gets the ID of the `Wire` the `Pin` is connected to.
+    pub fn get_wire(&self) -> usize {
+        self.wire
+    }
+}
+
+impl<'a> NetlistTransistor<'a> {
+    /// Gets the ID of the `NetlistDevice` for the `Transistor`.
+    pub fn get_device(&self) -> usize {
+        self.device
+    }
+
+    /// Gets the `Transistor` itself.
+    pub fn get_transistor(&self) -> &'a Transistor {
+        self.transistor
+    }
+
+    /// Gets the ID of the `Wire` the gate `Pin` is connected to.
+    pub fn get_gate(&self) -> usize {
+        self.gate
+    }
+
+    /// Gets the ID of the `Wire` the source `Pin` is connected to.
+    pub fn get_source(&self) -> usize {
+        self.source
+    }
+
+    /// Gets the ID of the `Wire` the drain `Pin` is connected to.
+    pub fn get_drain(&self) -> usize {
+        self.drain
+    }
+}
+
+impl<'a> NetlistConstant<'a> {
+    /// Gets the ID of the `NetlistDevice` for the `Constant`.
+    pub fn get_device(&self) -> usize {
+        self.device
+    }
+
+    /// Gets the `Constant` itself.
+    pub fn get_constant(&self) -> &'a Constant {
+        self.constant
+    }
+
+    /// Gets the ID of the `Wire` the output `Pin` is connected to.
+    pub fn get_output(&self) -> usize {
+        self.output
+    }
+}
+
+impl<'a> NetlistTestPin<'a> {
+    /// Gets the ID of the `NetlistDevice` for the `TestPin`.
+    pub fn get_device(&self) -> usize {
+        self.device
+    }
+
+    /// Gets the `TestPin` itself.
+    pub fn get_test_pin(&self) -> &'a TestPin {
+        self.test_pin
+    }
+
+    /// Gets the ID of the `Wire` the output `Pin` is connected to.
+    pub fn get_output(&self) -> usize {
+        self.output
+    }
+}
+
+impl<'a> NetlistClock<'a> {
+    /// Gets the ID of the `NetlistDevice` for the `Clock`.
+    pub fn get_device(&self) -> usize {
+        self.device
+    }
+
+    /// Gets the `Clock` itself.
+    pub fn get_clock(&self) -> &'a Clock {
+        self.clock
+    }
+
+    /// Gets the ID of the `Wire` the output `Pin` is on.
+    pub fn get_output(&self) -> usize {
+        self.output
+    }
+}
+
+impl NetlistWire {
+    /// Reads the current value of the `Wire`.
+    pub fn read(&self) -> LogicValue {
+        self.pins[0].borrow().read()
+    }
+
+    /// Gets all of the `Pin`s connected to the `Wire`.
+    pub fn get_pins(&self) -> &[Rc<RefCell<Pin>>] {
+        &self.pins
+    }
+
+    /// Gets what each of the `Pin`s belongs to, in the same order.
+    pub fn get_terminals(&self) -> &[Terminal] {
+        &self.terminals
+    }
+}
+
+impl<'a> Netlist<'a> {
+    /// Creates a new `Netlist` by walking the given `Device` hierarchy.
+    pub fn new(device: &'a dyn AnyDevice) -> Self {
+        let mut netlist = Self {
+            devices: vec![],
+            transistors: vec![],
+            constants: vec![],
+            test_pins: vec![],
+            clocks: vec![],
+            wires: vec![],
+            wire_indices: HashMap::new(),
+        };
+
+        // First find all of the devices and primitives, so we know what every `Pin` belongs to.
+        let mut terminals = HashMap::new();
+        netlist.add_device(String::new(), device, None, &mut terminals);
+
+        // Then find all of the wires, in the order the pins are found in the hierarchy.
+        for index in 0..netlist.devices.len() {
+            let mut pins = vec![];
+            for (name, container) in sorted(netlist.devices[index].device.pins()) {
+                match container {
+                    DeviceContainer::Single(pin) => pins.push((name, find_pin(&pin))),
+                    DeviceContainer::Multiple(multiple) => {
+                        for (pin_index, pin) in multiple.iter().enumerate() {
+                            pins.push((format!("{}[{}]", name, pin_index), find_pin(pin)));
+                        }
+                    }
+                }
+            }
+
+            for (name, (pin, connected_pins)) in pins {
+                let wire = netlist.add_wire(connected_pins, &terminals);
+                netlist.devices[index]
+                    .pins
+                    .push(NetlistPin { name, pin, wire });
+            }
+        }
+
+        // Finally, connect the primitives to their wires.
+        for transistor in netlist.transistors.iter_mut() {
+            let get_wire = |pin: &Rc<RefCell<Pin>>| netlist.wire_indices[&pin_key(pin)];
+            transistor.gate = get_wire(transistor.transistor.get_gate());
+            transistor.source = get_wire(transistor.transistor.get_source());
+            transistor.drain = get_wire(transistor.transistor.get_drain());
+        }
+        for constant in netlist.constants.iter_mut() {
+            constant.output = netlist.wire_indices[&pin_key(constant.constant.get_output())];
+        }
+        for test_pin in netlist.test_pins.iter_mut() {
+            test_pin.output = netlist.wire_indices[&pin_key(test_pin.test_pin.get_output())];
+        }
+        for clock in netlist.clocks.iter_mut() {
+            clock.output = netlist.wire_indices[&pin_key(clock.clock.get_output())];
+        }
+
+        netlist
+    }
+
+    /// Gets all of the devices in the hierarchy, including primitives. The root device has ID 0.
+    pub fn get_devices(&self) -> &[NetlistDevice<'a>] {
+        &self.devices
+    }
+
+    /// Gets all of the `Transistor`s in the hierarchy.
+    pub fn get_transistors(&self) -> &[NetlistTransistor<'a>] {
+        &self.transistors
+    }
+
+    /// Gets all of the `Constant`s in the hierarchy.
+    pub fn get_constants(&self) -> &[NetlistConstant<'a>] {
+        &self.constants
+    }
+
+    /// Gets all of the `TestPin`s in the hierarchy.
+    pub fn get_test_pins(&self) -> &[NetlistTestPin<'a>] {
+        &self.test_pins
+    }
+
+    /// Gets all of the `Clock`s in the hierarchy.
+    pub fn get_clocks(&self) -> &[NetlistClock<'a>] {
+        &self.clocks
+    }
+
+    /// Gets all of the `Wire`s in the hierarchy.
+    pub fn get_wires(&self) -> &[NetlistWire] {
+        &self.wires
+    }
+
+    /// Gets the ID of the `Wire` a `Pin` is connected to, or `None` if the `Pin` isn't connected to
+    /// any `Wire` in the hierarchy.
+    pub fn get_wire(&self, pin: &Pin) -> Option<usize> {
+        self.wire_indices.get(&(pin as *const Pin)).copied()
+    }
+
+    /// Gets the full hierarchical name of a device, e.g. `adders[3].or_gate.nmos[1]`. The root
+    /// device's name is empty.
+    pub fn get_path(&self, device: usize) -> String {
+        let mut names = vec![];
+        let mut current = Some(device);
+        while let Some(index) = current {
+            if self.devices[index].parent.is_some() {
+                names.push(self.devices[index].name.as_str());
+            }
+            current = self.devices[index].parent;
+        }
+        names.reverse();
+        names.join(".")
+    }
+
+    /// Recursively adds a device and all of its children to the `Netlist`, recording the `Pin`s of
+    /// any primitives in `terminals`.
+    fn add_device(
+        &mut self,
+        name: String,
+        device: &'a dyn AnyDevice,
+        parent: Option<usize>,
+        terminals: &mut HashMap<*const Pin, Terminal>,
+    ) -> usize {
+        let index = self.devices.len();
+        self.devices.push(NetlistDevice {
+            name,
+            device,
+            parent,
+            children: vec![],
+            pins: vec![],
+        });
+
+        let any = device as &dyn Any;
+        if let Some(transistor) = any.downcast_ref::<Transistor>() {
+            let id = self.transistors.len();
+            terminals.insert(pin_key(transistor.get_gate()), Terminal::Gate(id));
+            terminals.insert(pin_key(transistor.get_source()), Terminal::Source(id));
+            terminals.insert(pin_key(transistor.get_drain()), Terminal::Drain(id));
+            self.transistors.push(NetlistTransistor {
+                device: index,
+                transistor,
+                gate: 0,
+                source: 0,
+                drain: 0,
+            });
+        } else if let Some(constant) = any.downcast_ref::<Constant>() {
+            let id = self.constants.len();
+            terminals.insert(pin_key(constant.get_output()), Terminal::Constant(id));
+            self.constants.push(NetlistConstant {
+                device: index,
+                constant,
+                output: 0,
+            });
+        } else if let Some(test_pin) = any.downcast_ref::<TestPin>() {
+            let id = self.test_pins.len();
+            terminals.insert(pin_key(test_pin.get_output()), Terminal::TestPin(id));
+            self.test_pins.push(NetlistTestPin {
+                device: index,
+                test_pin,
+                output: 0,
+            });
+        } else if let Some(clock) = any.downcast_ref::<Clock>() {
+            let id = self.clocks.len();
+            terminals.insert(pin_key(clock.get_output()), Terminal::Clock(id));
+            self.clocks.push(NetlistClock {
+                device: index,
+                clock,
+                output: 0,
+            });
+        }
+        if let Some(behavioral) = device.behavioral() {
+            for pin in behavioral
+                .get_inputs()
+                .iter()
+                .chain(&behavioral.get_outputs())
+            {
+                terminals.insert(pin_key(pin), Terminal::Behavioral(index));
+            }
+        }
+
+        for (name, container) in sorted(device.children()) {
+            match container {
+                DeviceContainer::Single(child) => {
+                    let child = self.add_device(name, child, Some(index), terminals);
+                    self.devices[index].children.push(child);
+                }
+                DeviceContainer::Multiple(children) => {
+                    for (child_index, child) in children.into_iter().enumerate() {
+                        let name = format!("{}[{}]", name, child_index);
+                        let child = self.add_device(name, child, Some(index), terminals);
+                        self.devices[index].children.push(child);
+                    }
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Gets the ID of the `Wire` with the given connected `Pin`s, adding it if it hasn't been seen.
+    fn add_wire(
+        &mut self,
+        pins: Vec<Rc<RefCell<Pin>>>,
+        terminals: &HashMap<*const Pin, Terminal>,
+    ) -> usize {
+        if let Some(index) = self.wire_indices.get(&pin_key(&pins[0])) {
+            return *index;
+        }
+
+        let index = self.wires.len();
+        pins.iter().for_each(|pin| {
+            self.wire_indices.insert(pin_key(pin), index);
+        });
+        self.wires.push(NetlistWire {
+            terminals: pins
+                .iter()
+                .map(|pin| *terminals.get(&pin_key(pin)).unwrap_or(&Terminal::External))
+                .collect(),
+            pins,
+        });
+        index
+    }
+}
+
+/// Gets a key that uniquely identifies a `Pin`.
+fn pin_key(pin: &Rc<RefCell<Pin>>) -> *const Pin {
+    pin.as_ptr()
+}
+
+/// Finds the `Rc` for a borrowed `Pin` amongst the `Pin`s it is connected to. Returns the `Rc` as
+/// well as all of the connected `Pin`s.
+fn find_pin(pin: &Pin) -> (Rc<RefCell<Pin>>, Vec<Rc<RefCell<Pin>>>) {
+    let connected_pins = pin.get_connected_pins();
+    let found = connected_pins
+        .iter()
+        .find(|connected_pin| std::ptr::eq(pin_key(connected_pin), pin))
+        .expect("pin not connected to itself")
+        .clone();
+    (found, connected_pins)
+}
+
+/// Sorts the contents of a `pins` or `children` map by name, so that walking the hierarchy always
+/// happens in the same order.
+fn sorted<T>(map: HashMap<String, T>) -> Vec<(String, T)> {
+    let mut entries: Vec<(String, T)> = map.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TestNot;
+    use crate::{Behavioral, Device, DriveValue};
+    use device_derive::Device;
+
+    // A NOT gate with a `TestPin` built in to drive its input.
+    #[derive(Device)]
+    struct TestDevice {
+        #[child]
+        test_pin: TestPin,
+
+        #[child]
+        not_gate: TestNot,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestDevice {
+        fn new() -> Self {
+            let test_pin = TestPin::new(DriveValue::Strong(true));
+            let not_gate = TestNot::new();
+            let output = not_gate.get_output().clone();
+            Pin::connect(test_pin.get_output(), not_gate.get_input());
+            Self {
+                test_pin,
+                not_gate,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_netlist() {
+        let device = TestDevice::new();
+        let external_pin = TestPin::new(DriveValue::HighImpedance);
+        Pin::connect(external_pin.get_output(), device.get_output());
+        let netlist = Netlist::new(&device);
+
+        assert_eq!(netlist.get_devices().len(), 7);
+        assert_eq!(netlist.get_transistors().len(), 2);
+        assert_eq!(netlist.get_constants().len(), 2);
+        assert_eq!(netlist.get_test_pins().len(), 1);
+        assert_eq!(netlist.get_wires().len(), 4);
+
+        // The root device's pins are visited first.
+        assert_eq!(netlist.get_devices()[0].pins[0].name, "output");
+        assert_eq!(netlist.get_devices()[0].pins[0].wire, 0);
+        assert_eq!(netlist.get_wire(&device.get_output().borrow()), Some(0));
+        assert_eq!(
+            netlist.get_wire(&external_pin.get_output().borrow()),
+            Some(0)
+        );
+        assert_eq!(
+            netlist.get_wire(&Pin::new(DriveValue::HighImpedance).borrow()),
+            None
+        );
+
+        // Children are visited sorted by name.
+        let names: Vec<String> = (0..7).map(|index| netlist.get_path(index)).collect();
+        assert_eq!(
+            names,
+            [
+                "",
+                "not_gate",
+                "not_gate.nmos",
+                "not_gate.pmos",
+                "not_gate.strong_false",
+                "not_gate.strong_true",
+                "test_pin"
+            ]
+        );
+
+        let nmos = &netlist.get_transistors()[0];
+        let pmos = &netlist.get_transistors()[1];
+        assert_eq!(nmos.gate, pmos.gate);
+        assert_eq!(nmos.drain, pmos.drain);
+        assert_eq!(nmos.drain, 0);
+        assert_eq!(nmos.source, netlist.get_constants()[0].output);
+        assert_eq!(pmos.source, netlist.get_constants()[1].output);
+        assert_eq!(nmos.gate, netlist.get_test_pins()[0].output);
+        assert_eq!(
+            netlist.get_wires()[nmos.gate].read(),
+            LogicValue::Driven(true)
+        );
+
+        let mut output_terminals = netlist.get_wires()[0].terminals.clone();
+        output_terminals.sort_by_key(|terminal| format!("{:?}", terminal));
+        assert_eq!(
+            output_terminals,
+            [Terminal::Drain(0), Terminal::Drain(1), Terminal::External]
+        );
+    }
+
+    #[test]
+    fn test_netlist_stable_ids() {
+        let device_1 = TestDevice::new();
+        let device_2 = TestDevice::new();
+        let netlist_1 = Netlist::new(&device_1);
+        let netlist_2 = Netlist::new(&device_2);
+
+        for (transistor_1, transistor_2) in std::iter::zip(
+            netlist_1.get_transistors().iter(),
+            netlist_2.get_transistors().iter(),
+        ) {
+            assert_eq!(transistor_1.device, transistor_2.device);
+            assert_eq!(transistor_1.gate, transistor_2.gate);
+            assert_eq!(transistor_1.source, transistor_2.source);
+            assert_eq!(transistor_1.drain, transistor_2.drain);
+        }
+    }
+
+    // A buffer computed in Rust.
+    #[derive(Device)]
+    #[behavioral]
+    struct TestBuffer {
+        #[pin]
+        input: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl Behavioral for TestBuffer {
+        fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            vec![self.input.clone()]
+        }
+
+        fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            vec![self.output.clone()]
+        }
+
+        fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue> {
+            vec![inputs[0].into()]
+        }
+    }
+
+    // A `TestBuffer` driven by a `TestPin`.
+    #[derive(Device)]
+    struct TestBehavioralDevice {
+        #[child]
+        buffer: TestBuffer,
+
+        #[child]
+        test_pin: TestPin,
+    }
+
+    #[test]
+    fn test_netlist_behavioral() {
+        let buffer = TestBuffer {
+            input: Pin::new(DriveValue::HighImpedance),
+            output: Pin::new(DriveValue::HighImpedance),
+        };
+        let test_pin = TestPin::new(DriveValue::Strong(true));
+        Pin::connect(test_pin.get_output(), buffer.get_input());
+        let device = TestBehavioralDevice { buffer, test_pin };
+        let netlist = Netlist::new(&device);
+
+        assert_eq!(netlist.get_path(1), "buffer");
+        let input = netlist
+            .get_wire(&device.buffer.get_input().borrow())
+            .unwrap();
+        let output = netlist
+            .get_wire(&device.buffer.get_output().borrow())
+            .unwrap();
+        assert_eq!(
+            netlist.get_wires()[input].terminals,
+            [Terminal::TestPin(0), Terminal::Behavioral(1)]
+        );
+        assert_eq!(
+            netlist.get_wires()[output].terminals,
+            [Terminal::Behavioral(1)]
+        );
+    }
+}
@@ -46,8 +46,10 @@ impl Pin {
         self.wire.borrow().read()
     }
 
-    /// Creates a new `Pin` in the given initial state, connected to nothing.
-    pub(crate) fn new(initial_value: DriveValue) -> Rc<RefCell<Pin>> {
+    /// Creates a new `Pin` in the given initial state, connected to nothing. Outside of this crate
+    /// this is only useful for the `Pin`s of a `Behavioral` `Device`, since nothing else can change
+    /// the drive of the `Pin`.
+    pub fn new(initial_value: DriveValue) -> Rc<RefCell<Pin>> {
         let pin = Rc::new(RefCell::new(Pin {
             current_drive: initial_value,
             next_drive: None,
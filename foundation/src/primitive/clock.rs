@@ -0,0 +1,92 @@
+use crate::{AnyDevice, Device, DeviceContainer, DriveValue, Pin};
+use device_derive::Device;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A `Device` consisting of a single `Pin` which alternates between strongly driving `false` and
+/// `true`, for driving sequential logic.
+///
+/// The `Clock` starts out low, and only changes when it is driven by
+/// [`Simulator::run_clock`](crate::Simulator::run_clock), so ticking or settling a `Device` with a
+/// `Clock` in it works the same as if the `Clock` were a `Constant`.
+#[derive(Device)]
+pub struct Clock {
+    /// The pin the clock signal is driven onto.
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    /// The number of ticks the `Clock` stays high for in each cycle.
+    high_ticks: usize,
+
+    /// The number of ticks the `Clock` stays low for in each cycle.
+    low_ticks: usize,
+}
+
+impl Clock {
+    /// Creates a new `Clock` which stays high for `high_ticks` and low for `low_ticks` in each
+    /// cycle.
+    pub fn new(high_ticks: usize, low_ticks: usize) -> Self {
+        if high_ticks == 0 || low_ticks == 0 {
+            panic!("Clock high and low periods must be non-zero.")
+        }
+
+        Self {
+            output: Pin::new(DriveValue::Strong(false)),
+            high_ticks,
+            low_ticks,
+        }
+    }
+
+    /// Gets the number of ticks the `Clock` stays high for in each cycle.
+    pub fn get_high_ticks(&self) -> usize {
+        self.high_ticks
+    }
+
+    /// Gets the number of ticks the `Clock` stays low for in each cycle.
+    pub fn get_low_ticks(&self) -> usize {
+        self.low_ticks
+    }
+
+    /// Gets the number of ticks in each cycle.
+    pub fn get_period(&self) -> usize {
+        self.high_ticks + self.low_ticks
+    }
+
+    /// Drives the `Clock` high or low. Like `TestPin::set_drive`, this takes effect immediately.
+    pub(crate) fn set_level(&self, level: bool) {
+        let mut output = self.output.borrow_mut();
+        output.set_drive(DriveValue::Strong(level));
+        output.tick();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogicValue;
+
+    #[test]
+    fn test_clock() {
+        let clock = Clock::new(2, 3);
+        assert_eq!(clock.get_high_ticks(), 2);
+        assert_eq!(clock.get_low_ticks(), 3);
+        assert_eq!(clock.get_period(), 5);
+        assert_eq!(
+            clock.get_output().borrow().read(),
+            LogicValue::Driven(false)
+        );
+        clock.set_level(true);
+        assert_eq!(clock.get_output().borrow().read(), LogicValue::Driven(true));
+        clock.set_level(false);
+        assert_eq!(
+            clock.get_output().borrow().read(),
+            LogicValue::Driven(false)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_clock() {
+        Clock::new(0, 1);
+    }
+}
@@ -1,7 +1,9 @@
+mod clock;
 mod constant;
 mod test_pin;
 mod transistor;
 
+pub use clock::Clock;
 pub use constant::Constant;
 pub use test_pin::TestPin;
 pub use transistor::Transistor;
@@ -1,6 +1,6 @@
 use crate::{AnyDevice, Device, DeviceContainer, DriveValue, LogicValue, Pin};
 use device_derive::Device;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 /// Represents either an NMOS or PMOS transistor in an eletronic circuit.
@@ -43,7 +43,10 @@ pub struct Transistor {
     /// _then_ we will start outputing `LogicValue::Error`. This means error conditions must persist
     /// for at least two ticks before being reported. Errors that last only one tick should be
     /// invisible to the rest of the system, and this seems to be sufficient for everything to work.
-    error_hysteresis: bool,
+    ///
+    /// This is a `Cell` so that a `Transistor` can be ticked through a shared reference, which lets
+    /// the `Simulator` hold on to the `Transistor`s of a `Device` while it is still being read.
+    error_hysteresis: Cell<bool>,
 }
 
 impl Transistor {
@@ -66,11 +69,11 @@ impl Transistor {
     /// Returns `true` if the `Transistor`'s drain drive value changes, or if this is the first tick
     /// where the gate is high impedance / error and error hysteresis is being applied. Returns
     /// `false` otherwise.
-    pub(crate) fn tick(&mut self) -> bool {
+    pub(crate) fn tick(&self) -> bool {
         let current = LogicValue::from(self.drain.borrow().get_drive());
         let next = match self.gate.borrow().read() {
             LogicValue::Driven(drive) => {
-                self.error_hysteresis = false;
+                self.error_hysteresis.set(false);
                 if drive == self.activation {
                     self.source.borrow().read()
                 } else {
@@ -78,8 +81,8 @@ impl Transistor {
                 }
             }
             _ => {
-                if !self.error_hysteresis {
-                    self.error_hysteresis = true;
+                if !self.error_hysteresis.get() {
+                    self.error_hysteresis.set(true);
                     return true;
                 } else {
                     LogicValue::Error
@@ -91,6 +94,12 @@ impl Transistor {
         current != next
     }
 
+    /// Gets whether error hysteresis is currently being applied, i.e. the gate was high impedance
+    /// or an error on the last tick, but the drain hasn't been set to an error yet.
+    pub(crate) fn get_error_hysteresis(&self) -> bool {
+        self.error_hysteresis.get()
+    }
+
     // Private generic function for creating transistors.
     fn new(activation: bool) -> Self {
         Self {
@@ -98,7 +107,7 @@ impl Transistor {
             gate: Pin::new(DriveValue::HighImpedance),
             drain: Pin::new(DriveValue::HighImpedance),
             activation,
-            error_hysteresis: false,
+            error_hysteresis: Cell::new(false),
         }
     }
 }
@@ -0,0 +1,41 @@
+use crate::{DriveValue, LogicValue, Pin};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A `Device` whose outputs are computed by Rust code rather than by `Transistor`s.
+///
+/// A `Behavioral` `Device` owns its `Pin`s (see [`Pin::new`]), and the `Simulator` evaluates it
+/// alongside the `Transistor`s. Like a `Transistor`, it reads the current values of its inputs
+/// during the first phase of a tick, and the drives it returns for its outputs take effect in the
+/// second phase. It is only evaluated on the first tick, and on ticks after one of its inputs
+/// changed.
+///
+/// This is opt-in, a `Device` is only treated as `Behavioral` if [`crate::Device::behavioral`]
+/// returns it, which the `device_derive::Device` macro does for `struct`s marked `#[behavioral]`.
+/// This allows, for example, a large memory to be replaced by a model that is pin-compatible
+/// with the `Transistor`-level one, but much cheaper to simulate.
+///
+/// The `Simulator` only holds shared references to the `Device`, so any state must be kept in a
+/// `Cell` or `RefCell`.
+pub trait Behavioral {
+    /// Gets the `Pin`s whose values are passed to `evaluate`, in order.
+    fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>>;
+
+    /// Gets the `Pin`s whose drives are returned by `evaluate`, in order. A `Pin` can be both an
+    /// input and an output, e.g. a bidirectional data bus.
+    fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>>;
+
+    /// Computes the next drive of each of the outputs from the current value of each of the
+    /// inputs.
+    fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue>;
+
+    /// Gets a hash of any state kept between calls to `evaluate`, which is used by
+    /// [`crate::Simulator::settle_with_limit`] to tell whether the simulation has returned to an
+    /// earlier state. A `Device` without any state can return a constant.
+    ///
+    /// The default returns `None`, meaning the state is unknown, which turns off oscillation
+    /// detection for the whole simulation so `settle_with_limit` only gives up at its limit.
+    fn hash_state(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,368 @@
+mod behavioral;
+mod print;
+mod simulator;
+mod snapshot;
+mod vcd_recorder;
+
+pub use behavioral::Behavioral;
+pub use print::print;
+pub use simulator::{
+    settle, settle_with_limit, tick, ClockEdge, ClockMode, SettleError, Simulator,
+};
+pub use snapshot::{Snapshot, SnapshotDevice, SnapshotPin};
+pub use vcd_recorder::VcdRecorder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::BehavioralNot;
+    use crate::{
+        AnyDevice, Clock, Constant, Device, DeviceContainer, DriveValue, LogicValue, Pin, TestPin,
+        Transistor,
+    };
+    use device_derive::Device;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    // Used to show an empty device (i.e. a device with no pins or children) can nevertheless be
+    // simulated.
+    #[derive(Device)]
+    struct EmptyDevice {}
+
+    // A simple device that contains fields for pins and children with both single and multiple
+    // entries. This allows us to exercise most of the `device_derive::Device` procedural macro.
+    //
+    // Operationally this creates a not gate, but instead of tying the inputs together, the nmos
+    // and pmos inputs are separate (in order to test a vector of pins).
+    #[derive(Device)]
+    struct SimpleDevice {
+        #[child]
+        strong_true: Constant,
+
+        #[child]
+        strong_false: Constant,
+
+        #[children]
+        nmos: Vec<Transistor>,
+
+        #[child]
+        pmos: Transistor,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl SimpleDevice {
+        fn new() -> Self {
+            let strong_true = Constant::new_strong(true);
+            let strong_false = Constant::new_strong(false);
+            let nmos = vec![Transistor::new_nmos()];
+            let pmos = Transistor::new_pmos();
+            let input = vec![nmos[0].get_gate().clone(), pmos.get_gate().clone()];
+            let output = pmos.get_drain().clone();
+
+            Pin::connect(strong_false.get_output(), nmos[0].get_source());
+            Pin::connect(strong_true.get_output(), pmos.get_source());
+            Pin::connect(nmos[0].get_drain(), pmos.get_drain());
+
+            Self {
+                strong_true,
+                strong_false,
+                nmos,
+                pmos,
+                input,
+                output,
+            }
+        }
+    }
+
+    // Three NOT gates connected in a loop, which never settles.
+    #[derive(Device)]
+    struct RingOscillator {
+        #[children]
+        not_gates: Vec<SimpleDevice>,
+    }
+
+    impl RingOscillator {
+        fn new() -> Self {
+            let not_gates = vec![
+                SimpleDevice::new(),
+                SimpleDevice::new(),
+                SimpleDevice::new(),
+            ];
+            for (index, not_gate) in not_gates.iter().enumerate() {
+                let next = &not_gates[(index + 1) % not_gates.len()];
+                Pin::connect(not_gate.get_output(), &next.get_input()[0]);
+                Pin::connect(not_gate.get_output(), &next.get_input()[1]);
+            }
+            Self { not_gates }
+        }
+    }
+
+    // Drives its own input, inverting it a fixed number of times and then holding it. The wire
+    // repeats while it toggles, so only the count of remaining toggles shows it isn't oscillating.
+    #[derive(Device)]
+    #[behavioral]
+    struct BehavioralBlinker {
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+
+        toggles: Cell<usize>,
+
+        hashable: bool,
+    }
+
+    impl Behavioral for BehavioralBlinker {
+        fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            vec![self.output.clone()]
+        }
+
+        fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+            vec![self.output.clone()]
+        }
+
+        fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue> {
+            match inputs[0] {
+                LogicValue::Driven(value) if self.toggles.get() > 0 => {
+                    self.toggles.set(self.toggles.get() - 1);
+                    vec![DriveValue::Strong(!value)]
+                }
+                LogicValue::Driven(value) => vec![DriveValue::Strong(value)],
+                _ => vec![DriveValue::Strong(false)],
+            }
+        }
+
+        fn hash_state(&self) -> Option<u64> {
+            self.hashable.then(|| self.toggles.get() as u64)
+        }
+    }
+
+    // A behavioral NOT gate followed by a transistor NOT gate.
+    #[derive(Device)]
+    struct MixedDevice {
+        #[child]
+        behavioral_not: BehavioralNot,
+
+        #[child]
+        not_gate: SimpleDevice,
+    }
+
+    impl MixedDevice {
+        fn new() -> Self {
+            let behavioral_not = BehavioralNot::new();
+            let not_gate = SimpleDevice::new();
+            Pin::connect(behavioral_not.get_output(), &not_gate.get_input()[0]);
+            Pin::connect(behavioral_not.get_output(), &not_gate.get_input()[1]);
+            Self {
+                behavioral_not,
+                not_gate,
+            }
+        }
+    }
+
+    #[test]
+    fn empty_device() {
+        let mut empty_device = EmptyDevice {};
+        tick(&mut empty_device);
+        tick(&mut empty_device);
+        print(&empty_device, 2, true);
+    }
+
+    #[test]
+    fn simple_device() {
+        let mut simple_device = SimpleDevice::new();
+        simple_device.get_input()[0]
+            .borrow_mut()
+            .set_drive(DriveValue::Strong(true));
+        simple_device.get_input()[1]
+            .borrow_mut()
+            .set_drive(DriveValue::Strong(true));
+        assert_eq!(settle(&mut simple_device), 2);
+        assert_eq!(
+            LogicValue::Driven(false),
+            simple_device.get_output().borrow().read()
+        );
+        print(&simple_device, 0, false);
+    }
+
+    // A long-lived simulator must notice stimulus applied from outside of the device between ticks,
+    // and settle in the same number of ticks as a freshly created one.
+    #[test]
+    fn simulator_stimulus() {
+        let simple_device = SimpleDevice::new();
+        let mut test_pin = TestPin::new(DriveValue::HighImpedance);
+        Pin::connect(test_pin.get_output(), &simple_device.get_input()[0]);
+        Pin::connect(test_pin.get_output(), &simple_device.get_input()[1]);
+
+        let mut reference_device = SimpleDevice::new();
+        let mut reference_test_pin = TestPin::new(DriveValue::HighImpedance);
+        Pin::connect(
+            reference_test_pin.get_output(),
+            &reference_device.get_input()[0],
+        );
+        Pin::connect(
+            reference_test_pin.get_output(),
+            &reference_device.get_input()[1],
+        );
+
+        let mut simulator = Simulator::new(&simple_device);
+        assert_eq!(simulator.settle(), settle(&mut reference_device));
+        assert_eq!(
+            LogicValue::Error,
+            simple_device.get_output().borrow().read()
+        );
+
+        for value in [true, false, false, true, true] {
+            test_pin.set_drive(DriveValue::Strong(value));
+            reference_test_pin.set_drive(DriveValue::Strong(value));
+            assert_eq!(simulator.settle(), settle(&mut reference_device));
+            assert_eq!(
+                LogicValue::Driven(!value),
+                simple_device.get_output().borrow().read()
+            );
+        }
+    }
+
+    // The weak drive gives the ring a valid starting value, after which it oscillates forever.
+    #[test]
+    fn ring_oscillator() {
+        let ring_oscillator = RingOscillator::new();
+        let test_pin = TestPin::new(DriveValue::Weak(false));
+        Pin::connect(
+            test_pin.get_output(),
+            ring_oscillator.not_gates[0].get_output(),
+        );
+
+        let mut simulator = Simulator::new(&ring_oscillator);
+        let error = simulator.settle_with_limit(100).unwrap_err();
+        assert_eq!(error.get_ticks(), 10);
+        assert_eq!(error.get_period(), Some(6));
+        assert_eq!(
+            error.get_toggling_wires(),
+            [
+                "not_gates[0].input[0]",
+                "not_gates[0].output",
+                "not_gates[1].output"
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            "oscillating with a period of 6 ticks after 10 ticks, toggling not_gates[0].input[0], \
+             not_gates[0].output, not_gates[1].output"
+        );
+
+        let mut ring_oscillator = RingOscillator::new();
+        let test_pin = TestPin::new(DriveValue::Weak(false));
+        Pin::connect(
+            test_pin.get_output(),
+            ring_oscillator.not_gates[0].get_output(),
+        );
+        let error = settle_with_limit(&mut ring_oscillator, 3).unwrap_err();
+        assert_eq!(error.get_ticks(), 4);
+        assert_eq!(error.get_period(), None);
+
+        let mut simple_device = SimpleDevice::new();
+        assert_eq!(settle_with_limit(&mut simple_device, 100), Ok(2));
+
+        // Settling in exactly the limit is fine, but one tick fewer isn't.
+        let mut simple_device = SimpleDevice::new();
+        assert_eq!(settle_with_limit(&mut simple_device, 2), Ok(2));
+        let mut simple_device = SimpleDevice::new();
+        let error = settle_with_limit(&mut simple_device, 1).unwrap_err();
+        assert_eq!(error.get_ticks(), 2);
+        assert_eq!(error.get_period(), None);
+    }
+
+    // Inverts a clock, in both clock modes.
+    #[test]
+    fn clocked_simple_device() {
+        let simple_device = SimpleDevice::new();
+        let clock = Clock::new(3, 4);
+        Pin::connect(clock.get_output(), &simple_device.get_input()[0]);
+        Pin::connect(clock.get_output(), &simple_device.get_input()[1]);
+
+        let mut simulator = Simulator::new(&simple_device);
+        simulator.settle();
+        for mode in [ClockMode::Settle(10), ClockMode::Periods] {
+            let mut edges = vec![];
+            let ticks = simulator
+                .run_clock(&clock, 2, mode, &mut |edge, cycle| {
+                    let output = simple_device.get_output().borrow().read();
+                    edges.push((edge, cycle, output));
+                })
+                .unwrap();
+            let expected_ticks = match mode {
+                ClockMode::Settle(_) => 4,
+                ClockMode::Periods => 14,
+            };
+            assert_eq!(ticks, expected_ticks);
+            assert_eq!(
+                edges,
+                vec![
+                    (ClockEdge::Rising, 0, LogicValue::Driven(false)),
+                    (ClockEdge::Falling, 0, LogicValue::Driven(true)),
+                    (ClockEdge::Rising, 1, LogicValue::Driven(false)),
+                    (ClockEdge::Falling, 1, LogicValue::Driven(true)),
+                ]
+            );
+        }
+    }
+
+    // A behavioral device takes part in the simulation like a transistor, taking one tick to
+    // respond, and is only evaluated when its inputs change.
+    #[test]
+    fn behavioral_device() {
+        let mixed_device = MixedDevice::new();
+        let mut test_pin = TestPin::new(DriveValue::HighImpedance);
+        Pin::connect(
+            test_pin.get_output(),
+            mixed_device.behavioral_not.get_input(),
+        );
+        assert!(mixed_device.behavioral().is_none());
+        assert!(mixed_device.behavioral_not.behavioral().is_some());
+
+        let mut simulator = Simulator::new(&mixed_device);
+        simulator.settle();
+        assert_eq!(
+            mixed_device.not_gate.get_output().borrow().read(),
+            LogicValue::Error
+        );
+
+        for value in [true, false, true] {
+            test_pin.set_drive(DriveValue::Strong(value));
+            let evaluations = mixed_device.behavioral_not.get_evaluations();
+            assert_eq!(simulator.settle(), 2);
+            assert_eq!(
+                mixed_device.behavioral_not.get_evaluations(),
+                evaluations + 1
+            );
+            assert_eq!(
+                mixed_device.not_gate.get_output().borrow().read(),
+                LogicValue::Driven(value)
+            );
+        }
+
+        // Nothing changed, so nothing is evaluated.
+        let evaluations = mixed_device.behavioral_not.get_evaluations();
+        assert_eq!(simulator.settle(), 0);
+        assert_eq!(mixed_device.behavioral_not.get_evaluations(), evaluations);
+    }
+
+    // Behavioral state is part of the state used to detect oscillations, or turns detection off
+    // when it isn't known.
+    #[test]
+    fn behavioral_state() {
+        for hashable in [true, false] {
+            let mut blinker = BehavioralBlinker {
+                output: Pin::new(DriveValue::HighImpedance),
+                toggles: Cell::new(4),
+                hashable,
+            };
+            assert_eq!(settle_with_limit(&mut blinker, 100), Ok(5));
+            assert_eq!(blinker.toggles.get(), 0);
+        }
+    }
+}
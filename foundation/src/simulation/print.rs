@@ -0,0 +1,62 @@
+use crate::{AnyDevice, DeviceContainer, Pin};
+use std::{cell::Ref, ops::Deref};
+
+/// Prints a detailed recursive representation of a device to the console. Generates valid YAML in a
+/// dumb way. See [`Snapshot`](crate::Snapshot) for a machine-readable alternative which uses
+/// stable IDs rather than memory addresses.
+pub fn print(device: &dyn AnyDevice, level: usize, is_array_member: bool) {
+    let name = device.type_name();
+    if is_array_member {
+        println!("{:indent$}- type: {}", "", name, indent = level - 2);
+    } else {
+        println!("{:indent$}type: {}", "", device.type_name(), indent = level);
+    }
+
+    if !device.pins().is_empty() {
+        println!("{:indent$}pins:", "", indent = level);
+    }
+    for (name, pins) in device.pins().iter() {
+        let show_pin = |pin: &Ref<Pin>| {
+            println!(
+                "{:indent$}drive: {:?}",
+                "",
+                pin.get_drive(),
+                indent = level + 4,
+            );
+            println!("{:indent$}read: {:?}", "", pin.read(), indent = level + 4);
+            println!("{:indent$}id: {:p}", "", pin.deref(), indent = level + 4);
+            println!("{:indent$}connected:", "", indent = level + 4);
+            for connected_pin in pin.get_connected_pins().iter() {
+                println!(
+                    "{:indent$}- {:p}",
+                    "",
+                    connected_pin.borrow().deref(),
+                    indent = level + 6
+                );
+            }
+        };
+
+        println!("{:indent$}{}:", "", name, indent = level + 2);
+        match pins {
+            DeviceContainer::Single(pin) => show_pin(pin),
+            DeviceContainer::Multiple(pins) => pins.iter().for_each(show_pin),
+        }
+    }
+
+    if !device.children().is_empty() {
+        println!("{:indent$}children:", "", indent = level);
+    }
+    for (name, children) in device.children().iter() {
+        println!("{:indent$}{}:", "", name, indent = level + 2);
+        match children {
+            DeviceContainer::Single(child) => {
+                print(*child, level + 4, false);
+            }
+            DeviceContainer::Multiple(children) => {
+                for child in children.iter() {
+                    print(*child, level + 6, true);
+                }
+            }
+        }
+    }
+}
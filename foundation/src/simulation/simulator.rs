@@ -0,0 +1,603 @@
+use crate::{
+    AnyDevice, Behavioral, Clock, Constant, DeviceContainer, LogicValue, Netlist, Pin, Transistor,
+};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::zip;
+use std::rc::Rc;
+
+/// Moves the simulated `Transistors` and their associated `Pin`s and `Wire`s forward in time until
+/// there is a time step where nothing changes.
+///
+/// Returns the number of ticks it took to achieve being settled. This never returns if the
+/// `Device` oscillates, see [`settle_with_limit`] for a version which gives up.
+pub fn settle(device: &mut dyn AnyDevice) -> usize {
+    Simulator::new(device).settle()
+}
+
+/// Moves the simulated `Transistors` and their associated `Pin`s and `Wire`s forward in time until
+/// there is a time step where nothing changes, giving up if the `Device` is found to oscillate or
+/// if it doesn't settle within `limit` ticks.
+///
+/// Returns the number of ticks it took to achieve being settled, or a `SettleError` describing why
+/// it didn't settle.
+pub fn settle_with_limit(device: &mut dyn AnyDevice, limit: usize) -> Result<usize, SettleError> {
+    Simulator::new(device).settle_with_limit(limit)
+}
+
+/// The reason a `Device` did not settle, from [`settle_with_limit`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettleError {
+    /// The number of ticks that were simulated before giving up.
+    ticks: usize,
+
+    /// The number of ticks it takes for the state to repeat, if an oscillation was detected.
+    period: Option<usize>,
+
+    /// The full hierarchical names of the `Wire`s which were changing when the simulation gave up,
+    /// named after the first `Pin` found on each.
+    toggling_wires: Vec<String>,
+}
+
+impl SettleError {
+    /// Gets the number of ticks that were simulated before giving up.
+    pub fn get_ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Gets the number of ticks it takes for the state of the `Device` to repeat, or `None` if the
+    /// limit was reached without the state repeating.
+    pub fn get_period(&self) -> Option<usize> {
+        self.period
+    }
+
+    /// Gets the full hierarchical names of the `Wire`s which change during the oscillation, or
+    /// which changed on the last tick if no oscillation was detected, e.g. `not_gates[1].output`.
+    /// Each `Wire` is named after the first `Pin` found on it, starting from the root device.
+    pub fn get_toggling_wires(&self) -> &[String] {
+        &self.toggling_wires
+    }
+}
+
+impl fmt::Display for SettleError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.period {
+            Some(period) => write!(
+                formatter,
+                "oscillating with a period of {} ticks after {} ticks",
+                period, self.ticks
+            )?,
+            None => write!(formatter, "did not settle within {} ticks", self.ticks)?,
+        }
+        write!(formatter, ", toggling {}", self.toggling_wires.join(", "))
+    }
+}
+
+impl std::error::Error for SettleError {}
+
+/// How [`Simulator::run_clock`] moves the simulation forward after each clock edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockMode {
+    /// Settle after each edge, giving up if it takes more than the given number of ticks. The
+    /// periods of the `Clock` are ignored, so every edge happens once everything has settled.
+    Settle(usize),
+
+    /// Tick for exactly the high or low period of the `Clock` after each edge, whether or not
+    /// everything has settled, as a real clock would.
+    Periods,
+}
+
+/// An edge of a `Clock`, passed to the callback of [`Simulator::run_clock`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockEdge {
+    /// The `Clock` went from low to high.
+    Rising,
+
+    /// The `Clock` went from high to low.
+    Falling,
+}
+
+/// Moves all simulated `Transistors` and their associated `Pin`s and `Wire`s forward one time
+/// step.
+///
+/// A tick is split into two phases. First, we tick all of the `Transistors` (and evaluate all of
+/// the `Behavioral` `Device`s), which use all of the _current_ `Pin` and `Wire` states to perform
+/// their updates. The `Transistors` will set the next state on the `Pin`s, but that won't take
+/// effect until the `Pin` is ticked.
+///
+/// Returns `true` if anything changed during the tick. Returns `false` otherwise.
+pub fn tick(device: &mut dyn AnyDevice) -> bool {
+    Simulator::new(device).tick()
+}
+
+/// An event-driven simulator for a `Device`.
+///
+/// The `Device` hierarchy is flattened once when the `Simulator` is created. After that, rather
+/// than ticking every `Transistor` on every tick, the `Simulator` keeps track of which `Wire`s
+/// changed during the previous tick and only ticks the `Transistor`s whose gate or source is on
+/// one of those `Wire`s. Every other `Transistor` would just compute what it is already driving, so
+/// the results (and the number of ticks it takes to settle) are exactly the same as if every
+/// `Transistor` were ticked every time. `Behavioral` `Device`s are scheduled in the same way, and
+/// are evaluated whenever one of their inputs changed.
+///
+/// `Wire`s that can be changed from outside of the simulation, i.e. those with a `TestPin` or a
+/// `Pin` that isn't part of the `Device` at all, are checked at the start of every tick, so
+/// stimulus can be applied between ticks. Connecting `Pin`s after the `Simulator` is created is not
+/// supported, create a new `Simulator` instead.
+pub struct Simulator<'a> {
+    /// The `Device` being simulated.
+    device: &'a dyn AnyDevice,
+
+    /// All of the `Transistor`s in the `Device`.
+    transistors: Vec<&'a Transistor>,
+
+    /// The index of the `Wire` each `Transistor`'s gate is on, by `Transistor` index.
+    gates: Vec<usize>,
+
+    /// The index of the `Wire` each `Transistor`'s source is on, by `Transistor` index.
+    sources: Vec<usize>,
+
+    /// The index of the `Wire` each `Transistor`'s drain is on, by `Transistor` index.
+    drains: Vec<usize>,
+
+    /// A `Pin` on each `Wire`, used to read the `Wire`'s value.
+    wires: Vec<Rc<RefCell<Pin>>>,
+
+    /// The `Transistor`s whose gate or source is on each `Wire`, by `Wire` index.
+    readers: Vec<Vec<usize>>,
+
+    /// The value of each `Wire` as of the last time it was checked.
+    values: Vec<LogicValue>,
+
+    /// The `Wire`s which can be changed by something other than a `Transistor` in the `Device`.
+    external: Vec<usize>,
+
+    /// The `Transistor`s that must be ticked on the next tick.
+    scheduled: Vec<usize>,
+
+    /// Whether each `Transistor` is already in `scheduled`, to avoid ticking it twice.
+    is_scheduled: Vec<bool>,
+
+    /// All of the `Behavioral` `Device`s in the `Device`.
+    behaviorals: Vec<SimulatedBehavioral<'a>>,
+
+    /// The `Behavioral`s with an input on each `Wire`, by `Wire` index.
+    behavioral_readers: Vec<Vec<usize>>,
+
+    /// The `Behavioral`s that must be evaluated on the next tick.
+    scheduled_behaviorals: Vec<usize>,
+
+    /// Whether each `Behavioral` is already in `scheduled_behaviorals`, to avoid evaluating it
+    /// twice.
+    is_behavioral_scheduled: Vec<bool>,
+
+    /// Whether the first tick has happened yet.
+    started: bool,
+}
+
+/// A `Behavioral` `Device` in a `Simulator`, with its `Pin`s fetched once up front.
+struct SimulatedBehavioral<'a> {
+    /// The `Behavioral` itself.
+    behavioral: &'a dyn Behavioral,
+
+    /// The input `Pin`s of the `Behavioral`.
+    inputs: Vec<Rc<RefCell<Pin>>>,
+
+    /// The output `Pin`s of the `Behavioral`.
+    outputs: Vec<Rc<RefCell<Pin>>>,
+
+    /// The index of the `Wire` each output `Pin` is on.
+    output_wires: Vec<usize>,
+}
+
+impl<'a> Simulator<'a> {
+    /// Creates a new `Simulator` for the given `Device`. Every `Transistor` and `Behavioral` is
+    /// ticked on the first tick, since nothing is known about what changed before the `Simulator`
+    /// existed.
+    pub fn new(device: &'a dyn AnyDevice) -> Self {
+        let mut transistors = vec![];
+        let mut behaviorals = vec![];
+        let mut internal_pins = HashSet::new();
+        flatten(
+            device,
+            &mut transistors,
+            &mut behaviorals,
+            &mut internal_pins,
+        );
+
+        let mut simulator = Self {
+            device,
+            gates: Vec::with_capacity(transistors.len()),
+            sources: Vec::with_capacity(transistors.len()),
+            drains: Vec::with_capacity(transistors.len()),
+            wires: vec![],
+            readers: vec![],
+            values: vec![],
+            external: vec![],
+            scheduled: (0..transistors.len()).collect(),
+            is_scheduled: vec![true; transistors.len()],
+            behaviorals: vec![],
+            behavioral_readers: vec![],
+            scheduled_behaviorals: (0..behaviorals.len()).collect(),
+            is_behavioral_scheduled: vec![true; behaviorals.len()],
+            started: false,
+            transistors,
+        };
+
+        let mut wire_indices = HashMap::new();
+        for index in 0..simulator.transistors.len() {
+            let transistor = simulator.transistors[index];
+            let gate = simulator.add_wire(&mut wire_indices, &internal_pins, transistor.get_gate());
+            let source =
+                simulator.add_wire(&mut wire_indices, &internal_pins, transistor.get_source());
+            let drain =
+                simulator.add_wire(&mut wire_indices, &internal_pins, transistor.get_drain());
+            simulator.readers[gate].push(index);
+            if source != gate {
+                simulator.readers[source].push(index);
+            }
+            simulator.gates.push(gate);
+            simulator.sources.push(source);
+            simulator.drains.push(drain);
+        }
+
+        for (index, behavioral) in behaviorals.into_iter().enumerate() {
+            let inputs = behavioral.get_inputs();
+            let outputs = behavioral.get_outputs();
+            for input in inputs.iter() {
+                let wire = simulator.add_wire(&mut wire_indices, &internal_pins, input);
+                if !simulator.behavioral_readers[wire].contains(&index) {
+                    simulator.behavioral_readers[wire].push(index);
+                }
+            }
+            let output_wires = outputs
+                .iter()
+                .map(|output| simulator.add_wire(&mut wire_indices, &internal_pins, output))
+                .collect();
+            simulator.behaviorals.push(SimulatedBehavioral {
+                behavioral,
+                inputs,
+                outputs,
+                output_wires,
+            });
+        }
+
+        // Nobody needs to know about external changes to a wire that nothing reads.
+        let readers = &simulator.readers;
+        let behavioral_readers = &simulator.behavioral_readers;
+        simulator
+            .external
+            .retain(|wire| !readers[*wire].is_empty() || !behavioral_readers[*wire].is_empty());
+
+        simulator
+    }
+
+    /// Moves the simulation forward until there is a time step where nothing changes.
+    ///
+    /// Returns the number of ticks it took to achieve being settled.
+    pub fn settle(&mut self) -> usize {
+        let mut ticks: usize = 0;
+        while self.tick() {
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Moves the simulation forward until there is a time step where nothing changes, giving up
+    /// if the state of the `Device` repeats (i.e. it oscillates) or if it doesn't settle within
+    /// `limit` ticks.
+    ///
+    /// The state of every `Wire`, every driven `Pin`, every `Transistor`, and every `Behavioral`
+    /// `Device` is hashed after each tick, and since the simulation is deterministic, seeing the
+    /// same state twice means it will repeat forever. If a `Behavioral` `Device` doesn't provide a
+    /// hash of its state, oscillations can't be detected, and this only gives up at the limit.
+    ///
+    /// Returns the number of ticks it took to achieve being settled, which is at most `limit`, or a
+    /// `SettleError` describing why it didn't settle.
+    pub fn settle_with_limit(&mut self, limit: usize) -> Result<usize, SettleError> {
+        let mut ticks: usize = 0;
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        let mut history: Vec<Vec<usize>> = vec![];
+        loop {
+            let (changed, changed_wires) = self.tick_impl();
+            if !changed {
+                return Ok(ticks);
+            }
+            ticks += 1;
+            history.push(changed_wires);
+
+            let period = self
+                .hash_state()
+                .and_then(|hash| seen.insert(hash, ticks))
+                .map(|seen| ticks - seen);
+            if period.is_some() || ticks > limit {
+                let recent = &history[history.len() - period.unwrap_or(1)..];
+                let mut toggling: Vec<usize> = recent.iter().flatten().copied().collect();
+                toggling.sort();
+                toggling.dedup();
+                return Err(SettleError {
+                    ticks,
+                    period,
+                    toggling_wires: self.wire_names(&toggling),
+                });
+            }
+        }
+    }
+
+    /// Runs the simulation for the given number of cycles of a `Clock`, which can be part of the
+    /// `Device` or connected to it from outside. Each cycle starts with a rising edge and ends with
+    /// the `Clock` low again.
+    ///
+    /// After each edge the simulation is moved forward according to `mode`, and then `on_edge` is
+    /// called with the edge and the cycle number, starting from zero. This is the place to check
+    /// outputs, and to apply stimulus (e.g. using `TestPin`s) for the next edge.
+    ///
+    /// Returns the total number of ticks, or a `SettleError` if `mode` is `ClockMode::Settle` and
+    /// the simulation didn't settle after one of the edges.
+    pub fn run_clock(
+        &mut self,
+        clock: &Clock,
+        cycles: usize,
+        mode: ClockMode,
+        on_edge: &mut dyn FnMut(ClockEdge, usize),
+    ) -> Result<usize, SettleError> {
+        let mut ticks = 0;
+        for cycle in 0..cycles {
+            for (edge, level, period) in [
+                (ClockEdge::Rising, true, clock.get_high_ticks()),
+                (ClockEdge::Falling, false, clock.get_low_ticks()),
+            ] {
+                clock.set_level(level);
+                ticks += match mode {
+                    ClockMode::Settle(limit) => self.settle_with_limit(limit)?,
+                    ClockMode::Periods => {
+                        (0..period).for_each(|_| {
+                            self.tick();
+                        });
+                        period
+                    }
+                };
+                on_edge(edge, cycle);
+            }
+        }
+        Ok(ticks)
+    }
+
+    /// Moves the simulation forward one time step. Uses the same two phases as [`tick`], but only
+    /// for the `Transistor`s and `Behavioral`s which may have been affected by the previous tick.
+    ///
+    /// Returns `true` if anything changed during the tick. Returns `false` otherwise.
+    pub fn tick(&mut self) -> bool {
+        self.tick_impl().0
+    }
+
+    // Private function for ticking, which also returns the indices of the `Wire`s where any `Pin`
+    // changed.
+    fn tick_impl(&mut self) -> (bool, Vec<usize>) {
+        // Pick up any changes that were made from outside of the simulation since the last tick.
+        for index in 0..self.external.len() {
+            self.check_wire(self.external[index]);
+        }
+
+        let scheduled = std::mem::take(&mut self.scheduled);
+        scheduled
+            .iter()
+            .for_each(|index| self.is_scheduled[*index] = false);
+
+        // First phase, tick the transistors.
+        let ticked: Vec<bool> = scheduled
+            .iter()
+            .map(|index| self.transistors[*index].tick())
+            .collect();
+        let mut changed = ticked.iter().any(|ticked| *ticked);
+
+        // The behaviorals are part of the first phase too, so they must read their inputs before
+        // any pins are ticked.
+        let scheduled_behaviorals = std::mem::take(&mut self.scheduled_behaviorals);
+        for index in scheduled_behaviorals.iter() {
+            self.is_behavioral_scheduled[*index] = false;
+            let behavioral = &self.behaviorals[*index];
+            let inputs: Vec<LogicValue> = behavioral
+                .inputs
+                .iter()
+                .map(|input| input.borrow().read())
+                .collect();
+            let drives = behavioral.behavioral.evaluate(&inputs);
+            if drives.len() != behavioral.outputs.len() {
+                panic!("Behavioral returned the wrong number of drives.")
+            }
+            for (output, drive) in zip(behavioral.outputs.iter(), drives) {
+                output.borrow_mut().set_drive(drive);
+            }
+        }
+
+        // Second phase, tick the drain pins. A transistor that reported a change without changing
+        // its drain is applying error hysteresis, and must be ticked again next time.
+        let mut changed_wires = vec![];
+        for (index, ticked) in zip(scheduled.iter(), ticked.iter()) {
+            if self.transistors[*index].get_drain().borrow_mut().tick() {
+                changed = true;
+                changed_wires.push(self.drains[*index]);
+            } else if *ticked {
+                self.schedule(*index);
+            }
+        }
+        for index in scheduled_behaviorals.iter() {
+            let behavioral = &self.behaviorals[*index];
+            for (output, wire) in zip(behavioral.outputs.iter(), behavioral.output_wires.iter()) {
+                if output.borrow_mut().tick() {
+                    changed = true;
+                    changed_wires.push(*wire);
+                }
+            }
+        }
+
+        // Nothing in the simulation drives the gate and source pins, but they're ticked once in
+        // case they were given a drive before the simulation started.
+        if !self.started {
+            self.started = true;
+            for (index, transistor) in self.transistors.iter().enumerate() {
+                if transistor.get_gate().borrow_mut().tick() {
+                    changed = true;
+                    changed_wires.push(self.gates[index]);
+                }
+                if transistor.get_source().borrow_mut().tick() {
+                    changed = true;
+                    changed_wires.push(self.sources[index]);
+                }
+            }
+        }
+
+        changed_wires.iter().for_each(|wire| self.check_wire(*wire));
+
+        (changed, changed_wires)
+    }
+
+    /// Hashes everything which determines what happens on the next tick, i.e. the value of every
+    /// `Wire`, the drive of every drain `Pin` and `Behavioral` output `Pin`, the error hysteresis
+    /// of every `Transistor`, and the state of every `Behavioral` `Device`. Returns `None` if the
+    /// state of a `Behavioral` `Device` is unknown.
+    fn hash_state(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.values.hash(&mut hasher);
+        for transistor in self.transistors.iter() {
+            transistor
+                .get_drain()
+                .borrow()
+                .get_drive()
+                .hash(&mut hasher);
+            transistor.get_error_hysteresis().hash(&mut hasher);
+        }
+        for behavioral in self.behaviorals.iter() {
+            for output in behavioral.outputs.iter() {
+                output.borrow().get_drive().hash(&mut hasher);
+            }
+            behavioral.behavioral.hash_state()?.hash(&mut hasher);
+        }
+        Some(hasher.finish())
+    }
+
+    /// Gets the full hierarchical names of the given `Wire`s, sorted by name.
+    fn wire_names(&self, wires: &[usize]) -> Vec<String> {
+        let netlist = Netlist::new(self.device);
+        let mut names: Vec<Option<String>> = vec![None; netlist.get_wires().len()];
+        for (device, netlist_device) in netlist.get_devices().iter().enumerate() {
+            let path = netlist.get_path(device);
+            for pin in netlist_device.get_pins().iter() {
+                names[pin.get_wire()].get_or_insert_with(|| match path.as_str() {
+                    "" => pin.get_name().to_string(),
+                    path => format!("{}.{}", path, pin.get_name()),
+                });
+            }
+        }
+
+        let mut wire_names: Vec<String> = wires
+            .iter()
+            .filter_map(|wire| netlist.get_wire(&self.wires[*wire].borrow()))
+            .filter_map(|wire| names[wire].clone())
+            .collect();
+        wire_names.sort();
+        wire_names
+    }
+
+    /// Gets the index of the `Wire` the given `Pin` is on, adding the `Wire` if it hasn't been seen
+    /// before. `Wire`s are identified by the first of their connected `Pin`s.
+    fn add_wire(
+        &mut self,
+        wire_indices: &mut HashMap<*const RefCell<Pin>, usize>,
+        internal_pins: &HashSet<*const RefCell<Pin>>,
+        pin: &Rc<RefCell<Pin>>,
+    ) -> usize {
+        let connected_pins = pin.borrow().get_connected_pins();
+        let key = Rc::as_ptr(&connected_pins[0]);
+        if let Some(index) = wire_indices.get(&key) {
+            return *index;
+        }
+
+        let index = self.wires.len();
+        if connected_pins
+            .iter()
+            .any(|pin| !internal_pins.contains(&Rc::as_ptr(pin)))
+        {
+            self.external.push(index);
+        }
+        self.values.push(pin.borrow().read());
+        self.wires.push(pin.clone());
+        self.readers.push(vec![]);
+        self.behavioral_readers.push(vec![]);
+        wire_indices.insert(key, index);
+        index
+    }
+
+    /// Compares a `Wire`'s value to the last known value, and schedules every `Transistor` and
+    /// `Behavioral` which reads the `Wire` if it has changed.
+    fn check_wire(&mut self, wire: usize) {
+        let value = self.wires[wire].borrow().read();
+        if value != self.values[wire] {
+            self.values[wire] = value;
+            for index in 0..self.readers[wire].len() {
+                self.schedule(self.readers[wire][index]);
+            }
+            for index in 0..self.behavioral_readers[wire].len() {
+                let behavioral = self.behavioral_readers[wire][index];
+                if !self.is_behavioral_scheduled[behavioral] {
+                    self.is_behavioral_scheduled[behavioral] = true;
+                    self.scheduled_behaviorals.push(behavioral);
+                }
+            }
+        }
+    }
+
+    /// Schedules a `Transistor` to be ticked on the next tick.
+    fn schedule(&mut self, index: usize) {
+        if !self.is_scheduled[index] {
+            self.is_scheduled[index] = true;
+            self.scheduled.push(index);
+        }
+    }
+}
+
+/// Recursively goes through the `Device` hierarchy and collects all of the `Transistor`s and
+/// `Behavioral`s, as well as all of the `Pin`s which can only be changed by the simulation (i.e.
+/// `Transistor`, `Behavioral`, and `Constant` `Pin`s).
+fn flatten<'a>(
+    device: &'a dyn AnyDevice,
+    transistors: &mut Vec<&'a Transistor>,
+    behaviorals: &mut Vec<&'a dyn Behavioral>,
+    internal_pins: &mut HashSet<*const RefCell<Pin>>,
+) {
+    if let Some(behavioral) = device.behavioral() {
+        for pin in behavioral
+            .get_inputs()
+            .iter()
+            .chain(&behavioral.get_outputs())
+        {
+            internal_pins.insert(Rc::as_ptr(pin));
+        }
+        behaviorals.push(behavioral);
+    }
+
+    if let Some(transistor) = (device as &dyn Any).downcast_ref::<Transistor>() {
+        internal_pins.insert(Rc::as_ptr(transistor.get_source()));
+        internal_pins.insert(Rc::as_ptr(transistor.get_gate()));
+        internal_pins.insert(Rc::as_ptr(transistor.get_drain()));
+        transistors.push(transistor);
+    } else if let Some(constant) = (device as &dyn Any).downcast_ref::<Constant>() {
+        internal_pins.insert(Rc::as_ptr(constant.get_output()));
+    }
+
+    for (_, children) in device.children().into_iter() {
+        match children {
+            DeviceContainer::Single(child) => {
+                flatten(child, transistors, behaviorals, internal_pins)
+            }
+            DeviceContainer::Multiple(children) => children
+                .into_iter()
+                .for_each(|child| flatten(child, transistors, behaviorals, internal_pins)),
+        }
+    }
+}
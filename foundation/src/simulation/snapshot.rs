@@ -0,0 +1,434 @@
+use crate::{AnyDevice, DriveValue, LogicValue, Netlist, Pin};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// A snapshot of the state of every `Pin` in a `Device` hierarchy, which can be written as JSON or
+/// YAML, e.g. so circuit states can be compared between runs.
+///
+/// Unlike [`print()`](crate::print), `Pin`s and `Wire`s are identified by stable integer IDs rather
+/// than memory addresses, so snapshots of the same `Device` constructed the same way are identical
+/// if the state is identical. `Pin`s with the same ID are the same `Pin` (e.g. a `Pin` a device
+/// shares with one of its children), and `Pin`s with the same `Wire` ID are connected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// The snapshot of the root device.
+    root: SnapshotDevice,
+}
+
+/// The state of a device in a `Snapshot`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotDevice {
+    /// The name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    name: String,
+
+    /// The `type_name` of the device.
+    type_name: String,
+
+    /// The state of the device's `Pin`s, sorted by name.
+    pins: Vec<SnapshotPin>,
+
+    /// The snapshots of the device's children, sorted by name.
+    children: Vec<SnapshotDevice>,
+}
+
+/// The state of a `Pin` in a `Snapshot`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotPin {
+    /// The name of the `Pin` within its device, e.g. `output` or `input[1]`.
+    name: String,
+
+    /// The ID of the `Pin`, which is the same everywhere the `Pin` appears.
+    id: usize,
+
+    /// The ID of the `Wire` the `Pin` is connected to.
+    wire: usize,
+
+    /// The `DriveValue` the `Pin` is driving onto the `Wire`.
+    drive: DriveValue,
+
+    /// The `LogicValue` read from the `Wire`.
+    read: LogicValue,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the current state of the given `Device`.
+    pub fn new(device: &dyn AnyDevice) -> Self {
+        let netlist = Netlist::new(device);
+        let mut pin_ids = HashMap::new();
+        Self {
+            root: snapshot_device(&netlist, 0, &mut pin_ids),
+        }
+    }
+
+    /// Gets the snapshot of the root device.
+    pub fn get_root(&self) -> &SnapshotDevice {
+        &self.root
+    }
+
+    /// Writes the snapshot as JSON.
+    pub fn write_json(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write_json_device(writer, &self.root, 0)?;
+        writeln!(writer)
+    }
+
+    /// Writes the snapshot as YAML.
+    pub fn write_yaml(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write_yaml_device(writer, &self.root, "", "")
+    }
+}
+
+impl SnapshotDevice {
+    /// Gets the name of the device within its parent, e.g. `adders[3]`. Empty for the root device.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the `type_name` of the device.
+    pub fn get_type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Gets the state of the device's `Pin`s, sorted by name.
+    pub fn get_pins(&self) -> &[SnapshotPin] {
+        &self.pins
+    }
+
+    /// Gets the snapshots of the device's children, sorted by name.
+    pub fn get_children(&self) -> &[SnapshotDevice] {
+        &self.children
+    }
+}
+
+impl SnapshotPin {
+    /// Gets the name of the `Pin` within its device, e.g. `output` or `input[1]`.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the ID of the `Pin`, which is the same everywhere the `Pin` appears.
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    /// Gets the ID of the `Wire` the `Pin` is connected to.
+    pub fn get_wire(&self) -> usize {
+        self.wire
+    }
+
+    /// Gets the `DriveValue` the `Pin` is driving onto the `Wire`.
+    pub fn get_drive(&self) -> DriveValue {
+        self.drive
+    }
+
+    /// Gets the `LogicValue` read from the `Wire`.
+    pub fn get_read(&self) -> LogicValue {
+        self.read
+    }
+}
+
+/// Recursively takes a snapshot of a device in the `Netlist`. `Pin`s are given IDs in the order
+/// they're first seen.
+fn snapshot_device(
+    netlist: &Netlist,
+    device: usize,
+    pin_ids: &mut HashMap<*const Pin, usize>,
+) -> SnapshotDevice {
+    let netlist_device = &netlist.get_devices()[device];
+    let pins = netlist_device
+        .get_pins()
+        .iter()
+        .map(|pin| {
+            let next_id = pin_ids.len();
+            let borrowed = pin.get_pin().borrow();
+            SnapshotPin {
+                name: pin.get_name().to_string(),
+                id: *pin_ids.entry(&*borrowed as *const Pin).or_insert(next_id),
+                wire: pin.get_wire(),
+                drive: borrowed.get_drive(),
+                read: borrowed.read(),
+            }
+        })
+        .collect();
+    let children = netlist_device
+        .get_children()
+        .iter()
+        .map(|child| snapshot_device(netlist, *child, pin_ids))
+        .collect();
+
+    SnapshotDevice {
+        name: netlist_device.get_name().to_string(),
+        type_name: netlist_device.get_device().type_name(),
+        pins,
+        children,
+    }
+}
+
+/// Writes a device as a JSON object, at the given indentation level.
+fn write_json_device(
+    writer: &mut dyn Write,
+    device: &SnapshotDevice,
+    level: usize,
+) -> io::Result<()> {
+    let indent = "  ".repeat(level);
+    writeln!(writer, "{{")?;
+    writeln!(writer, "{}  \"name\": {},", indent, quote(&device.name))?;
+    writeln!(
+        writer,
+        "{}  \"type\": {},",
+        indent,
+        quote(&device.type_name)
+    )?;
+
+    if device.pins.is_empty() {
+        writeln!(writer, "{}  \"pins\": [],", indent)?;
+    } else {
+        writeln!(writer, "{}  \"pins\": [", indent)?;
+        for (index, pin) in device.pins.iter().enumerate() {
+            let separator = if index + 1 < device.pins.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(
+                writer,
+                "{}    {{\"name\": {}, \"id\": {}, \"wire\": {}, \"drive\": {}, \"read\": {}}}{}",
+                indent,
+                quote(&pin.name),
+                pin.id,
+                pin.wire,
+                quote(&format!("{:?}", pin.drive)),
+                quote(&format!("{:?}", pin.read)),
+                separator
+            )?;
+        }
+        writeln!(writer, "{}  ],", indent)?;
+    }
+
+    if device.children.is_empty() {
+        writeln!(writer, "{}  \"children\": []", indent)?;
+    } else {
+        writeln!(writer, "{}  \"children\": [", indent)?;
+        for (index, child) in device.children.iter().enumerate() {
+            write!(writer, "{}    ", indent)?;
+            write_json_device(writer, child, level + 2)?;
+            if index + 1 < device.children.len() {
+                writeln!(writer, ",")?;
+            } else {
+                writeln!(writer)?;
+            }
+        }
+        writeln!(writer, "{}  ]", indent)?;
+    }
+
+    write!(writer, "{}}}", indent)
+}
+
+/// Writes a device as a YAML mapping. Every line is indented by `indent`, and the first line is
+/// also prefixed with `marker`, e.g. `- ` to make the device a sequence entry.
+fn write_yaml_device(
+    writer: &mut dyn Write,
+    device: &SnapshotDevice,
+    indent: &str,
+    marker: &str,
+) -> io::Result<()> {
+    writeln!(writer, "{}{}name: {}", indent, marker, quote(&device.name))?;
+    let indent = format!("{}{}", indent, " ".repeat(marker.len()));
+    writeln!(writer, "{}type: {}", indent, quote(&device.type_name))?;
+
+    if device.pins.is_empty() {
+        writeln!(writer, "{}pins: []", indent)?;
+    } else {
+        writeln!(writer, "{}pins:", indent)?;
+    }
+    for pin in device.pins.iter() {
+        writeln!(writer, "{}  - name: {}", indent, quote(&pin.name))?;
+        writeln!(writer, "{}    id: {}", indent, pin.id)?;
+        writeln!(writer, "{}    wire: {}", indent, pin.wire)?;
+        writeln!(writer, "{}    drive: {:?}", indent, pin.drive)?;
+        writeln!(writer, "{}    read: {:?}", indent, pin.read)?;
+    }
+
+    if device.children.is_empty() {
+        writeln!(writer, "{}children: []", indent)?;
+    } else {
+        writeln!(writer, "{}children:", indent)?;
+    }
+    for child in device.children.iter() {
+        write_yaml_device(writer, child, &format!("{}  ", indent), "- ")?;
+    }
+
+    Ok(())
+}
+
+/// Quotes a string, escaping it so it is valid in both JSON and YAML.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constant, Device, DeviceContainer, TestPin, Transistor};
+    use device_derive::Device;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // An NMOS transistor which pulls its output low when enabled.
+    #[derive(Device)]
+    struct TestPullDown {
+        #[child]
+        strong_false: Constant,
+
+        #[child]
+        nmos: Transistor,
+
+        #[pin]
+        enable: Rc<RefCell<Pin>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestPullDown {
+        fn new() -> Self {
+            let strong_false = Constant::new_strong(false);
+            let nmos = Transistor::new_nmos();
+            let enable = nmos.get_gate().clone();
+            let output = nmos.get_drain().clone();
+            Pin::connect(strong_false.get_output(), nmos.get_source());
+
+            Self {
+                strong_false,
+                nmos,
+                enable,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let device = TestPullDown::new();
+        let mut test_pin = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(test_pin.get_output(), device.get_enable());
+        let mut simulator = crate::Simulator::new(&device);
+        simulator.settle();
+        let before = Snapshot::new(&device);
+        assert_eq!(before, Snapshot::new(&device));
+
+        test_pin.set_drive(DriveValue::Strong(true));
+        simulator.settle();
+        let after = Snapshot::new(&device);
+        assert_ne!(before, after);
+
+        let root = after.get_root();
+        assert_eq!(root.get_type_name(), "TestPullDown");
+        assert_eq!(root.get_pins()[1].get_name(), "output");
+        assert_eq!(root.get_pins()[1].get_read(), LogicValue::Driven(false));
+        assert_eq!(root.get_children()[0].get_name(), "nmos");
+        assert_eq!(
+            root.get_children()[0].get_pins()[0].get_id(),
+            root.get_pins()[1].get_id()
+        );
+        assert_eq!(
+            root.get_children()[1].get_pins()[0].get_wire(),
+            root.get_children()[0].get_pins()[2].get_wire()
+        );
+
+        let mut json = vec![];
+        after.write_json(&mut json).unwrap();
+        let expected = r#"{
+  "name": "",
+  "type": "TestPullDown",
+  "pins": [
+    {"name": "enable", "id": 0, "wire": 0, "drive": "HighImpedance", "read": "Driven(true)"},
+    {"name": "output", "id": 1, "wire": 1, "drive": "Strong(false)", "read": "Driven(false)"}
+  ],
+  "children": [
+    {
+      "name": "nmos",
+      "type": "Transistor",
+      "pins": [
+        {"name": "drain", "id": 1, "wire": 1, "drive": "Strong(false)", "read": "Driven(false)"},
+        {"name": "gate", "id": 0, "wire": 0, "drive": "HighImpedance", "read": "Driven(true)"},
+        {"name": "source", "id": 2, "wire": 2, "drive": "HighImpedance", "read": "Driven(false)"}
+      ],
+      "children": []
+    },
+    {
+      "name": "strong_false",
+      "type": "Constant",
+      "pins": [
+        {"name": "output", "id": 3, "wire": 2, "drive": "Strong(false)", "read": "Driven(false)"}
+      ],
+      "children": []
+    }
+  ]
+}
+"#;
+        assert_eq!(String::from_utf8(json).unwrap(), expected);
+
+        let mut yaml = vec![];
+        after.write_yaml(&mut yaml).unwrap();
+        let expected = r#"name: ""
+type: "TestPullDown"
+pins:
+  - name: "enable"
+    id: 0
+    wire: 0
+    drive: HighImpedance
+    read: Driven(true)
+  - name: "output"
+    id: 1
+    wire: 1
+    drive: Strong(false)
+    read: Driven(false)
+children:
+  - name: "nmos"
+    type: "Transistor"
+    pins:
+      - name: "drain"
+        id: 1
+        wire: 1
+        drive: Strong(false)
+        read: Driven(false)
+      - name: "gate"
+        id: 0
+        wire: 0
+        drive: HighImpedance
+        read: Driven(true)
+      - name: "source"
+        id: 2
+        wire: 2
+        drive: HighImpedance
+        read: Driven(false)
+    children: []
+  - name: "strong_false"
+    type: "Constant"
+    pins:
+      - name: "output"
+        id: 3
+        wire: 2
+        drive: Strong(false)
+        read: Driven(false)
+    children: []
+"#;
+        assert_eq!(String::from_utf8(yaml).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("adders[3]"), "\"adders[3]\"");
+        assert_eq!(quote("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+}
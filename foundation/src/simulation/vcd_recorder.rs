@@ -0,0 +1,330 @@
+use crate::{AnyDevice, DriveValue, LogicValue, Netlist, Simulator};
+use std::io::{self, Write};
+
+/// Records the values of every named `Pin` in a `Device` hierarchy while it is simulated, and
+/// writes them out as an IEEE 1364 Value Change Dump (VCD) file which can be viewed in tools like
+/// GTKWave.
+///
+/// Every device in the hierarchy becomes a scope named after its field in the parent device (the
+/// root scope is named after the root device's type), and every `Pin` becomes a variable within
+/// that scope. `Pin`s on the same `Wire` share an identifier, since they always have the same
+/// value. Each tick is one time unit.
+///
+/// Optionally the `DriveValue` of every `Pin` can be recorded as well. VCD has no way to represent
+/// drive strength, so these are recorded as `string` variables with a `_drive` suffix, which
+/// GTKWave understands but other tools may not.
+pub struct VcdRecorder<'a> {
+    /// The flattened `Device` hierarchy, used to find all of the named `Pin`s.
+    netlist: Netlist<'a>,
+
+    /// Used to move the `Device` forward in time.
+    simulator: Simulator<'a>,
+
+    /// Whether the `DriveValue` of every `Pin` is recorded as well as the `LogicValue`s.
+    record_drives: bool,
+
+    /// The current time, i.e. the number of ticks so far.
+    time: usize,
+
+    /// The last recorded value of every `Wire`, by `Wire` ID.
+    wire_values: Vec<LogicValue>,
+
+    /// The last recorded drive of every `Pin`, in the order they are found in the `Netlist`.
+    pin_drives: Vec<DriveValue>,
+
+    /// Every recorded change as `(time, changes)`, where each change is `(identifier, value)`.
+    /// The first entry holds the initial value of everything.
+    changes: Vec<(usize, Vec<(usize, String)>)>,
+}
+
+impl<'a> VcdRecorder<'a> {
+    /// Creates a new `VcdRecorder` for the given `Device`, recording only `LogicValue`s.
+    pub fn new(device: &'a dyn AnyDevice) -> Self {
+        Self::new_impl(device, false)
+    }
+
+    /// Creates a new `VcdRecorder` for the given `Device`, recording `DriveValue`s as well as
+    /// `LogicValue`s.
+    pub fn new_with_drives(device: &'a dyn AnyDevice) -> Self {
+        Self::new_impl(device, true)
+    }
+
+    /// Moves the simulation forward one time step, recording any changes. Any stimulus applied
+    /// since the last tick is recorded at the time the tick starts.
+    ///
+    /// Returns `true` if anything changed during the tick. Returns `false` otherwise.
+    pub fn tick(&mut self) -> bool {
+        self.record();
+        let changed = self.simulator.tick();
+        self.time += 1;
+        self.record();
+        changed
+    }
+
+    /// Moves the simulation forward until there is a time step where nothing changes, recording
+    /// any changes.
+    ///
+    /// Returns the number of ticks it took to achieve being settled.
+    pub fn settle(&mut self) -> usize {
+        let mut ticks: usize = 0;
+        while self.tick() {
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Writes all of the recorded changes as a VCD file.
+    pub fn write(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "$version logic foundation $end")?;
+        writeln!(writer, "$timescale 1ns $end")?;
+        let mut pin_index = 0;
+        self.write_scope(writer, 0, &mut pin_index)?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        for (index, (time, changes)) in self.changes.iter().enumerate() {
+            writeln!(writer, "#{}", time)?;
+            if index == 0 {
+                writeln!(writer, "$dumpvars")?;
+            }
+            for (identifier, value) in changes.iter() {
+                writeln!(
+                    writer,
+                    "{}",
+                    format_change(&identifier_code(*identifier), value)
+                )?;
+            }
+            if index == 0 {
+                writeln!(writer, "$end")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Private generic function for creating recorders.
+    fn new_impl(device: &'a dyn AnyDevice, record_drives: bool) -> Self {
+        let netlist = Netlist::new(device);
+        let wire_values: Vec<LogicValue> =
+            netlist.get_wires().iter().map(|wire| wire.read()).collect();
+        let pin_drives: Vec<DriveValue> = netlist
+            .get_devices()
+            .iter()
+            .flat_map(|device| device.get_pins().iter())
+            .map(|pin| pin.get_pin().borrow().get_drive())
+            .collect();
+
+        let mut initial: Vec<(usize, String)> = wire_values
+            .iter()
+            .enumerate()
+            .map(|(wire, value)| (wire, format_logic_value(*value)))
+            .collect();
+        if record_drives {
+            initial.extend(
+                pin_drives
+                    .iter()
+                    .enumerate()
+                    .map(|(pin, drive)| (wire_values.len() + pin, format_drive_value(*drive))),
+            );
+        }
+
+        Self {
+            simulator: Simulator::new(device),
+            netlist,
+            record_drives,
+            time: 0,
+            wire_values,
+            pin_drives,
+            changes: vec![(0, initial)],
+        }
+    }
+
+    /// Records anything that changed since the last time anything was recorded.
+    fn record(&mut self) {
+        let mut changes = vec![];
+
+        for (wire, netlist_wire) in self.netlist.get_wires().iter().enumerate() {
+            let value = netlist_wire.read();
+            if value != self.wire_values[wire] {
+                self.wire_values[wire] = value;
+                changes.push((wire, format_logic_value(value)));
+            }
+        }
+
+        if self.record_drives {
+            let pins = self
+                .netlist
+                .get_devices()
+                .iter()
+                .flat_map(|device| device.get_pins().iter());
+            for (index, pin) in pins.enumerate() {
+                let drive = pin.get_pin().borrow().get_drive();
+                if drive != self.pin_drives[index] {
+                    self.pin_drives[index] = drive;
+                    changes.push((self.wire_values.len() + index, format_drive_value(drive)));
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+
+        // Something may change more than once at the same time, e.g. stimulus applied right after
+        // a tick, in which case only the final value is kept.
+        match self.changes.last_mut() {
+            Some((time, existing)) if *time == self.time => {
+                existing.retain(|(identifier, _)| {
+                    !changes.iter().any(|(changed, _)| changed == identifier)
+                });
+                existing.append(&mut changes);
+            }
+            _ => self.changes.push((self.time, changes)),
+        }
+    }
+
+    /// Recursively writes the scope for a device, and all of its children.
+    fn write_scope(
+        &self,
+        writer: &mut dyn Write,
+        device: usize,
+        pin_index: &mut usize,
+    ) -> io::Result<()> {
+        let netlist_device = &self.netlist.get_devices()[device];
+        let name = match netlist_device.get_parent() {
+            Some(_) => netlist_device.get_name().to_string(),
+            None => netlist_device.get_device().type_name(),
+        };
+        writeln!(writer, "$scope module {} $end", name)?;
+
+        for pin in netlist_device.get_pins().iter() {
+            writeln!(
+                writer,
+                "$var wire 1 {} {} $end",
+                identifier_code(pin.get_wire()),
+                pin.get_name()
+            )?;
+            if self.record_drives {
+                let drive_name = match pin.get_name().find('[') {
+                    Some(index) => format!(
+                        "{}_drive{}",
+                        &pin.get_name()[..index],
+                        &pin.get_name()[index..]
+                    ),
+                    None => format!("{}_drive", pin.get_name()),
+                };
+                writeln!(
+                    writer,
+                    "$var string 1 {} {} $end",
+                    identifier_code(self.wire_values.len() + *pin_index),
+                    drive_name
+                )?;
+            }
+            *pin_index += 1;
+        }
+
+        for child in netlist_device.get_children().iter() {
+            self.write_scope(writer, *child, pin_index)?;
+        }
+
+        writeln!(writer, "$upscope $end")
+    }
+}
+
+/// Converts an index into a VCD identifier code, using the printable ASCII characters.
+fn identifier_code(mut index: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return code;
+        }
+        index -= 1;
+    }
+}
+
+/// Formats a single value change line. Scalar values are written immediately before the
+/// identifier, while string values are prefixed with `s` and separated by a space.
+fn format_change(identifier: &str, value: &str) -> String {
+    if value.len() == 1 {
+        format!("{}{}", value, identifier)
+    } else {
+        format!("s{} {}", value, identifier)
+    }
+}
+
+/// Formats a `LogicValue` as a four state VCD value.
+fn format_logic_value(value: LogicValue) -> String {
+    match value {
+        LogicValue::Driven(true) => "1",
+        LogicValue::Driven(false) => "0",
+        LogicValue::HighImpedance => "z",
+        LogicValue::Error => "x",
+    }
+    .to_string()
+}
+
+/// Formats a `DriveValue` as a VCD string value.
+fn format_drive_value(value: DriveValue) -> String {
+    format!("{:?}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Constant, Pin, TestPin, Transistor};
+
+    #[test]
+    fn test_identifier_code() {
+        assert_eq!(identifier_code(0), "!");
+        assert_eq!(identifier_code(93), "~");
+        assert_eq!(identifier_code(94), "!!");
+        assert_eq!(identifier_code(95), "\"!");
+        assert_eq!(identifier_code(94 + 94 * 94), "!!!");
+    }
+
+    #[test]
+    fn test_vcd_recorder() {
+        let nmos = Transistor::new_nmos();
+        let strong_true = Constant::new_strong(true);
+        let mut test_pin = TestPin::new(DriveValue::Strong(false));
+        Pin::connect(strong_true.get_output(), nmos.get_source());
+        Pin::connect(test_pin.get_output(), nmos.get_gate());
+
+        let mut recorder = VcdRecorder::new_with_drives(&nmos);
+        assert_eq!(recorder.settle(), 0);
+        test_pin.set_drive(DriveValue::Strong(true));
+        assert_eq!(recorder.settle(), 1);
+
+        let mut output = vec![];
+        recorder.write(&mut output).unwrap();
+        let expected = "\
+$version logic foundation $end
+$timescale 1ns $end
+$scope module Transistor $end
+$var wire 1 ! drain $end
+$var string 1 $ drain_drive $end
+$var wire 1 \" gate $end
+$var string 1 % gate_drive $end
+$var wire 1 # source $end
+$var string 1 & source_drive $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+z!
+0\"
+1#
+sHighImpedance $
+sHighImpedance %
+sHighImpedance &
+$end
+#1
+1\"
+#2
+1!
+sStrong(true) $
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
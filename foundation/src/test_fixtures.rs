@@ -0,0 +1,120 @@
+//! Devices shared by the tests of several modules.
+
+use crate::{
+    AnyDevice, Behavioral, Constant, Device, DeviceContainer, DriveValue, LogicValue, Pin,
+    Transistor,
+};
+use device_derive::Device;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// A NOT gate built from transistors, optionally with a weak pull-down on the output.
+#[derive(Device)]
+pub struct TestNot {
+    #[child]
+    strong_true: Constant,
+
+    #[child]
+    strong_false: Constant,
+
+    #[children]
+    weak_false: Vec<Constant>,
+
+    #[child]
+    nmos: Transistor,
+
+    #[child]
+    pmos: Transistor,
+
+    #[pin]
+    input: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+}
+
+impl TestNot {
+    /// Creates a NOT gate without a pull-down.
+    pub fn new() -> Self {
+        Self::new_impl(vec![])
+    }
+
+    /// Creates a NOT gate with a weak pull-down on the output.
+    pub fn with_weak_pull_down() -> Self {
+        Self::new_impl(vec![Constant::new_weak(false)])
+    }
+
+    fn new_impl(weak_false: Vec<Constant>) -> Self {
+        let strong_true = Constant::new_strong(true);
+        let strong_false = Constant::new_strong(false);
+        let nmos = Transistor::new_nmos();
+        let pmos = Transistor::new_pmos();
+        let input = nmos.get_gate().clone();
+        let output = nmos.get_drain().clone();
+
+        Pin::connect(nmos.get_gate(), pmos.get_gate());
+        Pin::connect(nmos.get_drain(), pmos.get_drain());
+        Pin::connect(strong_false.get_output(), nmos.get_source());
+        Pin::connect(strong_true.get_output(), pmos.get_source());
+        for constant in weak_false.iter() {
+            Pin::connect(nmos.get_drain(), constant.get_output());
+        }
+
+        Self {
+            strong_true,
+            strong_false,
+            weak_false,
+            nmos,
+            pmos,
+            input,
+            output,
+        }
+    }
+}
+
+/// A NOT gate computed in Rust, which counts how many times it has been evaluated.
+#[derive(Device)]
+#[behavioral]
+pub struct BehavioralNot {
+    #[pin]
+    input: Rc<RefCell<Pin>>,
+
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+
+    evaluations: Cell<usize>,
+}
+
+impl BehavioralNot {
+    /// Creates a NOT gate which hasn't been evaluated yet.
+    pub fn new() -> Self {
+        Self {
+            input: Pin::new(DriveValue::HighImpedance),
+            output: Pin::new(DriveValue::HighImpedance),
+            evaluations: Cell::new(0),
+        }
+    }
+
+    /// Gets the number of times the NOT gate has been evaluated.
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations.get()
+    }
+}
+
+impl Behavioral for BehavioralNot {
+    fn get_inputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+        vec![self.input.clone()]
+    }
+
+    fn get_outputs(&self) -> Vec<Rc<RefCell<Pin>>> {
+        vec![self.output.clone()]
+    }
+
+    fn evaluate(&self, inputs: &[LogicValue]) -> Vec<DriveValue> {
+        self.evaluations.set(self.evaluations.get() + 1);
+        match inputs[0] {
+            LogicValue::Driven(value) => vec![DriveValue::Strong(!value)],
+            _ => vec![DriveValue::Error],
+        }
+    }
+}
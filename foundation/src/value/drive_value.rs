@@ -18,7 +18,7 @@ use crate::LogicValue;
 ///      If no `Strong` value is set, but a `Weak` value is set, then that will be the value of the
 ///      `Wire`. If neither a `Strong` nor a `Weak` value is set, then the next rule will apply.
 ///   5. If all pins are `DriveValue::HighImpedance` then the `Wire` is `LogicValue::HighImpedance`.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum DriveValue {
     /// The `Pin` is strongly driving high/true/1 or low/false/0.
     Strong(bool),
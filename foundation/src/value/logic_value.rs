@@ -2,7 +2,7 @@ use crate::DriveValue;
 
 /// The simulated electrical states a `Wire` can resolve to, taking into account all `Pins`
 /// connected together on the `Wire`.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum LogicValue {
     /// The `Wire` is being driven high/true/1 or low/false/0.
     Driven(bool),
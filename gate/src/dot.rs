@@ -0,0 +1,31 @@
+use crate::gate_primitive;
+use foundation::{write_dot_collapsed, AnyDevice};
+use std::io::{self, Write};
+
+/// Writes a `Device` hierarchy as a Graphviz DOT graph, drawing each of the gates in this crate as
+/// a single node rather than showing their transistors.
+pub fn write_gate_dot(device: &dyn AnyDevice, writer: &mut dyn Write) -> io::Result<()> {
+    write_dot_collapsed(device, writer, &|device, _| {
+        gate_primitive(device).is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotGate;
+
+    #[test]
+    fn test_write_gate_dot() {
+        let mut output = vec![];
+        write_gate_dot(&NotGate::new(), &mut output).unwrap();
+        let expected = "\
+graph {
+  rankdir=LR;
+  node [shape=record];
+  d0 [label=\"{NotGate|{<p0> input|<p1> output}}\"];
+}
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
@@ -73,92 +73,45 @@ impl NandGate {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use foundation::{settle, DriveValue, LogicValue, TestPin, DRIVE_VALUES};
+    use foundation::{check_truth_table, DriveValue, LogicValue, TruthTableValues};
+
+    // The output is low if every input is high, and an error if any input isn't driven.
+    fn expected(drives: &[DriveValue]) -> Vec<LogicValue> {
+        let mut output = LogicValue::Driven(false);
+        for drive in drives.iter() {
+            match (LogicValue::from(*drive), output) {
+                (LogicValue::Driven(false), LogicValue::Driven(_)) => {
+                    output = LogicValue::Driven(true)
+                }
+                (LogicValue::Driven(true), _) => (),
+                _ => output = LogicValue::Error,
+            }
+        }
+        vec![output]
+    }
 
     #[test]
     fn test_nand_gate_2_input() {
-        let get_expected =
-            |a: &DriveValue, b: &DriveValue| match (LogicValue::from(*a), LogicValue::from(*b)) {
-                (LogicValue::Driven(false), LogicValue::Driven(false)) => LogicValue::Driven(true),
-                (LogicValue::Driven(false), LogicValue::Driven(true)) => LogicValue::Driven(true),
-                (LogicValue::Driven(true), LogicValue::Driven(false)) => LogicValue::Driven(true),
-                (LogicValue::Driven(true), LogicValue::Driven(true)) => LogicValue::Driven(false),
-                _ => LogicValue::Error,
-            };
-
-        let mut nand_gate = NandGate::new(2);
-        let mut test_pin_a = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_b = TestPin::new(DriveValue::HighImpedance);
-        Pin::connect(test_pin_a.get_output(), &nand_gate.get_input()[0]);
-        Pin::connect(test_pin_b.get_output(), &nand_gate.get_input()[1]);
-
-        for value_a in DRIVE_VALUES.iter() {
-            for value_b in DRIVE_VALUES.iter() {
-                test_pin_a.set_drive(*value_a);
-                test_pin_b.set_drive(*value_b);
-                settle(&mut nand_gate);
-                let actual = nand_gate.get_output().borrow().read();
-                let expected = get_expected(value_a, value_b);
-                assert_eq!(expected, actual);
-            }
-        }
+        let mismatches = check_truth_table(
+            NandGate::new(2),
+            &["input"],
+            &["output"],
+            TruthTableValues::DriveValues,
+            expected,
+        );
+        assert_eq!(mismatches, []);
     }
 
     #[test]
     fn test_nand_gate_3_input() {
-        let get_expected = |a: &DriveValue, b: &DriveValue, c: &DriveValue| match (
-            LogicValue::from(*a),
-            LogicValue::from(*b),
-            LogicValue::from(*c),
-        ) {
-            (LogicValue::Driven(false), LogicValue::Driven(false), LogicValue::Driven(false)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(false), LogicValue::Driven(false), LogicValue::Driven(true)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(false), LogicValue::Driven(true), LogicValue::Driven(false)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(false), LogicValue::Driven(true), LogicValue::Driven(true)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(true), LogicValue::Driven(false), LogicValue::Driven(false)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(true), LogicValue::Driven(false), LogicValue::Driven(true)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(true), LogicValue::Driven(true), LogicValue::Driven(false)) => {
-                LogicValue::Driven(true)
-            }
-            (LogicValue::Driven(true), LogicValue::Driven(true), LogicValue::Driven(true)) => {
-                LogicValue::Driven(false)
-            }
-            _ => LogicValue::Error,
-        };
-
-        let mut nand_gate = NandGate::new(3);
-        let mut test_pin_a = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_b = TestPin::new(DriveValue::HighImpedance);
-        let mut test_pin_c = TestPin::new(DriveValue::HighImpedance);
-        Pin::connect(test_pin_a.get_output(), &nand_gate.get_input()[0]);
-        Pin::connect(test_pin_b.get_output(), &nand_gate.get_input()[1]);
-        Pin::connect(test_pin_c.get_output(), &nand_gate.get_input()[2]);
-
-        for value_a in DRIVE_VALUES.iter() {
-            for value_b in DRIVE_VALUES.iter() {
-                for value_c in DRIVE_VALUES.iter() {
-                    test_pin_a.set_drive(*value_a);
-                    test_pin_b.set_drive(*value_b);
-                    test_pin_c.set_drive(*value_c);
-                    settle(&mut nand_gate);
-                    let actual = nand_gate.get_output().borrow().read();
-                    let expected = get_expected(value_a, value_b, value_c);
-                    assert_eq!(expected, actual);
-                }
-            }
-        }
+        let mismatches = check_truth_table(
+            NandGate::new(3),
+            &["input"],
+            &["output"],
+            TruthTableValues::DriveValues,
+            expected,
+        );
+        assert_eq!(mismatches, []);
     }
 
     #[test]
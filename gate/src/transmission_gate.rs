@@ -0,0 +1,101 @@
+use device_derive::Device;
+use foundation::{AnyDevice, Device, DeviceContainer, Pin, Transistor};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A gate made from an NMOS and a PMOS transistor in parallel, which passes its input through to
+/// its output when `enable` is high and `enable_inverted` is low, and disconnects it otherwise.
+///
+/// Unlike a `TriStateBufferGate`, the input is never connected to a transistor gate, so while the
+/// gate is disabled its output is high impedance whatever the input is, even if it's an error.
+/// That makes it suitable for breaking the feedback loops in latches. Normally `enable_inverted` is
+/// driven by the inverse of `enable`, but they're separate so the inverse can be shared.
+#[derive(Device)]
+pub struct TransmissionGate {
+    #[child]
+    nmos: Transistor,
+    #[child]
+    pmos: Transistor,
+    #[pin]
+    enable: Rc<RefCell<Pin>>,
+    #[pin]
+    enable_inverted: Rc<RefCell<Pin>>,
+    #[pin]
+    input: Rc<RefCell<Pin>>,
+    #[pin]
+    output: Rc<RefCell<Pin>>,
+}
+
+impl TransmissionGate {
+    /// Construct a new transmission gate.
+    pub fn new() -> Self {
+        let nmos = Transistor::new_nmos();
+        let pmos = Transistor::new_pmos();
+        let enable = nmos.get_gate().clone();
+        let enable_inverted = pmos.get_gate().clone();
+        let input = nmos.get_source().clone();
+        let output = nmos.get_drain().clone();
+
+        Pin::connect(nmos.get_source(), pmos.get_source());
+        Pin::connect(nmos.get_drain(), pmos.get_drain());
+
+        Self {
+            nmos,
+            pmos,
+            enable,
+            enable_inverted,
+            input,
+            output,
+        }
+    }
+}
+
+impl Default for TransmissionGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundation::{settle, DriveValue, LogicValue, TestPin, DRIVE_VALUES};
+
+    #[test]
+    fn test_transmission_gate() {
+        let get_expected =
+            |enable: &DriveValue, enable_inverted: &DriveValue, input: &DriveValue| match (
+                LogicValue::from(*enable),
+                LogicValue::from(*enable_inverted),
+            ) {
+                (LogicValue::Driven(false), LogicValue::Driven(true)) => LogicValue::HighImpedance,
+                (LogicValue::Driven(_), LogicValue::Driven(_)) => LogicValue::from(*input),
+                _ => LogicValue::Error,
+            };
+
+        let mut transmission_gate = TransmissionGate::default();
+        let mut test_pin_enable = TestPin::new(DriveValue::HighImpedance);
+        let mut test_pin_enable_inverted = TestPin::new(DriveValue::HighImpedance);
+        let mut test_pin_input = TestPin::new(DriveValue::HighImpedance);
+        Pin::connect(test_pin_enable.get_output(), transmission_gate.get_enable());
+        Pin::connect(
+            test_pin_enable_inverted.get_output(),
+            transmission_gate.get_enable_inverted(),
+        );
+        Pin::connect(test_pin_input.get_output(), transmission_gate.get_input());
+
+        for value_enable in DRIVE_VALUES.iter() {
+            for value_enable_inverted in DRIVE_VALUES.iter() {
+                for value_input in DRIVE_VALUES.iter() {
+                    test_pin_enable.set_drive(*value_enable);
+                    test_pin_enable_inverted.set_drive(*value_enable_inverted);
+                    test_pin_input.set_drive(*value_input);
+                    settle(&mut transmission_gate);
+                    let actual = transmission_gate.get_output().borrow().read();
+                    let expected = get_expected(value_enable, value_enable_inverted, value_input);
+                    assert_eq!(expected, actual);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{
+    AndGate, BufferGate, NandGate, NorGate, NotGate, OrGate, TransmissionGate, TriStateBufferGate,
+    XnorGate, XorGate,
+};
+use foundation::{write_verilog_with_primitives, AnyDevice, VerilogPrimitive};
+use std::any::Any;
+use std::io::{self, Write};
+
+/// Writes a `Device` hierarchy as gate-level Verilog, suitable for synthesis.
+///
+/// This is the same as `foundation::write_verilog`, except the gates in this crate are written as
+/// Verilog gate primitives rather than descending into their transistors. Note that the enable of
+/// a `TriStateBufferGate` is active low, so it is written as `bufif0`, and a `TransmissionGate` is
+/// written as the `cmos` switch primitive.
+pub fn write_gate_verilog(device: &dyn AnyDevice, writer: &mut dyn Write) -> io::Result<()> {
+    write_verilog_with_primitives(device, writer, &gate_primitive)
+}
+
+/// Gets the Verilog gate primitive for a device, if it is one of the gates in this crate.
+pub fn gate_primitive(device: &dyn AnyDevice) -> Option<VerilogPrimitive> {
+    let any = device as &dyn Any;
+    let (primitive, inputs) = if let Some(gate) = any.downcast_ref::<AndGate>() {
+        ("and", vector_inputs(gate.get_input().len()))
+    } else if let Some(gate) = any.downcast_ref::<NandGate>() {
+        ("nand", vector_inputs(gate.get_input().len()))
+    } else if let Some(gate) = any.downcast_ref::<OrGate>() {
+        ("or", vector_inputs(gate.get_input().len()))
+    } else if let Some(gate) = any.downcast_ref::<NorGate>() {
+        ("nor", vector_inputs(gate.get_input().len()))
+    } else if any.is::<XorGate>() {
+        ("xor", vec!["a_input".to_string(), "b_input".to_string()])
+    } else if any.is::<XnorGate>() {
+        ("xnor", vec!["a_input".to_string(), "b_input".to_string()])
+    } else if any.is::<NotGate>() {
+        ("not", vec!["input".to_string()])
+    } else if any.is::<BufferGate>() {
+        ("buf", vec!["input".to_string()])
+    } else if any.is::<TriStateBufferGate>() {
+        ("bufif0", vec!["input".to_string(), "enable".to_string()])
+    } else if any.is::<TransmissionGate>() {
+        (
+            "cmos",
+            vec![
+                "input".to_string(),
+                "enable".to_string(),
+                "enable_inverted".to_string(),
+            ],
+        )
+    } else {
+        return None;
+    };
+
+    // The output always comes first in Verilog gate primitives.
+    let mut pins = vec!["output".to_string()];
+    pins.extend(inputs);
+    Some(VerilogPrimitive { primitive, pins })
+}
+
+/// Gets the names of the `Pin`s of an `input` vector with the given number of inputs.
+fn vector_inputs(num_inputs: usize) -> Vec<String> {
+    (0..num_inputs)
+        .map(|index| format!("input[{}]", index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use device_derive::Device;
+    use foundation::{Device, DeviceContainer, Pin};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Passes the AND of three inputs through a tri-state buffer, controlled by the XOR of two more
+    // inputs.
+    #[derive(Device)]
+    struct TestDevice {
+        #[child]
+        and_gate: AndGate,
+
+        #[child]
+        xor_gate: XorGate,
+
+        #[child]
+        buffer_gate: TriStateBufferGate,
+
+        #[pins]
+        input: Vec<Rc<RefCell<Pin>>>,
+
+        #[pin]
+        output: Rc<RefCell<Pin>>,
+    }
+
+    impl TestDevice {
+        fn new() -> Self {
+            let and_gate = AndGate::new(3);
+            let xor_gate = XorGate::new();
+            let buffer_gate = TriStateBufferGate::new();
+            let mut input = and_gate.get_input().clone();
+            input.push(xor_gate.get_a_input().clone());
+            input.push(xor_gate.get_b_input().clone());
+            let output = buffer_gate.get_output().clone();
+
+            Pin::connect(and_gate.get_output(), buffer_gate.get_input());
+            Pin::connect(xor_gate.get_output(), buffer_gate.get_enable());
+
+            Self {
+                and_gate,
+                xor_gate,
+                buffer_gate,
+                input,
+                output,
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_gate_verilog() {
+        let device = TestDevice::new();
+        let mut output = vec![];
+        write_gate_verilog(&device, &mut output).unwrap();
+        let expected = "\
+module TestDevice (\\input , \\output );
+  inout [4:0] \\input ;
+  inout \\output ;
+  wire w0;
+  wire w1;
+  and and_gate (w0, \\input [0], \\input [1], \\input [2]);
+  bufif0 buffer_gate (\\output , w0, w1);
+  xor xor_gate (w1, \\input [3], \\input [4]);
+endmodule
+
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // A `TransmissionGate` with its pins renamed, so the order of the `cmos` ports can be seen.
+    #[derive(Device)]
+    struct TestSwitch {
+        #[child]
+        transmission_gate: TransmissionGate,
+
+        #[pin]
+        data: Rc<RefCell<Pin>>,
+
+        #[pin]
+        bus: Rc<RefCell<Pin>>,
+
+        #[pin]
+        select: Rc<RefCell<Pin>>,
+
+        #[pin]
+        select_inverted: Rc<RefCell<Pin>>,
+    }
+
+    impl TestSwitch {
+        fn new() -> Self {
+            let transmission_gate = TransmissionGate::new();
+            let data = transmission_gate.get_input().clone();
+            let bus = transmission_gate.get_output().clone();
+            let select = transmission_gate.get_enable().clone();
+            let select_inverted = transmission_gate.get_enable_inverted().clone();
+            Self {
+                transmission_gate,
+                data,
+                bus,
+                select,
+                select_inverted,
+            }
+        }
+    }
+
+    // Verilog's `cmos` takes the output, the input, the n-channel control and then the p-channel
+    // control.
+    #[test]
+    fn test_write_gate_verilog_transmission_gate() {
+        let device = TestSwitch::new();
+        let mut output = vec![];
+        write_gate_verilog(&device, &mut output).unwrap();
+        let expected = "\
+module TestSwitch (bus, data, select, select_inverted);
+  inout bus;
+  inout data;
+  inout select;
+  inout select_inverted;
+  cmos transmission_gate (bus, data, select, select_inverted);
+endmodule
+
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_gate_primitive() {
+        let primitive = |device: &dyn AnyDevice| gate_primitive(device).unwrap().primitive;
+        assert_eq!(primitive(&AndGate::new(2)), "and");
+        assert_eq!(primitive(&NandGate::new(2)), "nand");
+        assert_eq!(primitive(&OrGate::new(2)), "or");
+        assert_eq!(primitive(&NorGate::new(2)), "nor");
+        assert_eq!(primitive(&NotGate::new()), "not");
+        assert_eq!(primitive(&BufferGate::new()), "buf");
+        assert_eq!(primitive(&XorGate::new()), "xor");
+        assert_eq!(primitive(&XnorGate::new()), "xnor");
+        assert_eq!(primitive(&TriStateBufferGate::new()), "bufif0");
+        assert_eq!(primitive(&TransmissionGate::new()), "cmos");
+        assert!(gate_primitive(&foundation::Transistor::new_nmos()).is_none());
+    }
+}